@@ -0,0 +1,545 @@
+//! Beveling of edges and vertices.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::data::{Index, Position, VertexAttributes};
+use crate::elements::Point;
+use crate::handles::{FaceHandle, HalfEdgeHandle, PointHandle, VertexHandle};
+use crate::mesh::Mesh;
+use crate::operators::{Fan, Polygon};
+use crate::traits::*;
+
+/// What happens to an edge at a beveled point.
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Cut {
+    /// The edge keeps ending at the point.
+    Keep,
+    /// The edge is shortened, ending at a new point along it.
+    Slide,
+    /// The edge is replaced by a strip of faces.
+    Bevel,
+}
+
+/// Corners of a polygon together with their attributes.
+type Corners = Vec<(PointHandle, VertexAttributes)>;
+
+/// Point on the quadratic Bézier curve from `a` to `b` with `control`.
+fn profile_position(a: &Position, control: &Position, b: &Position, t: f32) -> Position {
+    let coords = a.coords * (1.0 - t).powi(2)
+        + control.coords * (2.0 * t * (1.0 - t))
+        + b.coords * t.powi(2);
+    Position::from(coords)
+}
+
+/// Splits a closed loop of points into simple loops wherever a point is
+/// visited twice, dropping loops with less than three corners.
+fn simple_loops(corners: Corners) -> Vec<Corners> {
+    let mut corners: Corners = corners.into_iter().fold(Vec::new(), |mut corners, corner| {
+        if corners.last().map(|(point, _)| *point) != Some(corner.0) {
+            corners.push(corner);
+        }
+        corners
+    });
+    while corners.len() > 1 && corners[0].0 == corners[corners.len() - 1].0 {
+        corners.pop();
+    }
+
+    let mut loops = Vec::new();
+    let mut stack: Corners = Vec::new();
+    for corner in corners {
+        match stack.iter().position(|(point, _)| *point == corner.0) {
+            Some(start) => {
+                loops.push(stack.split_off(start + 1));
+                loops.last_mut().unwrap().insert(0, stack[start].clone());
+            }
+            None => stack.push(corner),
+        }
+    }
+    loops.push(stack);
+    loops.retain(|corners| corners.len() >= 3);
+    loops
+}
+
+/// New geometry around the beveled points, collected before the affected
+/// faces are rebuilt.
+#[derive(Default)]
+struct Bevel {
+    /// Corners replacing the start of a half-edge in its face.
+    cuts: HashMap<Index, Corners>,
+    /// Profile across a beveled edge at the start of a half-edge, leading
+    /// from the face of the half-edge to the face of its twin.
+    profiles: HashMap<Index, Corners>,
+    faces: Vec<FaceHandle>,
+    polygons: Vec<Polygon>,
+}
+
+impl Mesh {
+    fn add_point(&mut self, position: Position) -> PointHandle {
+        self.add(Point::new(position))
+    }
+
+    /// Cuts the corners of all faces around a vertex, filling the hole
+    /// left around the point with polygons unless it is on the boundary.
+    ///
+    /// Without beveled edges every face corner gets a profile between the
+    /// points slid along its edges.
+    fn cut_fan(
+        &mut self,
+        bevel: &mut Bevel,
+        fan: &Fan,
+        kinds: &[Cut],
+        width: f32,
+        segments: usize,
+    ) {
+        let count = fan.edges.len();
+        let (point, position) = {
+            let vert = self.edge(fan.edges[0]).vertex();
+            (vert.point().handle, vert.point().position())
+        };
+        let rounded_corners = !kinds.contains(&Cut::Bevel);
+        let (directions, lengths): (Vec<_>, Vec<_>) = fan
+            .edges
+            .iter()
+            .map(|edge| {
+                let end = self.edge(*edge).adjacent().vertex().point().position();
+                let length = (end - position).norm();
+                ((end - position) / length.max(f32::EPSILON), length)
+            })
+            .unzip();
+        let attributes: Vec<_> = fan
+            .edges
+            .iter()
+            .map(|edge| self.vertex_attributes(self.edge(*edge).vertex().handle))
+            .collect();
+
+        let mut slid = HashMap::new();
+        for index in 0..count {
+            if kinds[index] != Cut::Slide {
+                continue;
+            }
+            let distances: Vec<f32> = fan
+                .neighbors(index)
+                .into_iter()
+                .filter(|neighbor| kinds[*neighbor] == Cut::Bevel)
+                .map(|neighbor| {
+                    let sine = directions[index].cross(&directions[neighbor]).norm();
+                    width / sine.max(f32::EPSILON)
+                })
+                .collect();
+            let distance = if distances.is_empty() {
+                width
+            } else {
+                distances.iter().sum::<f32>() / distances.len() as f32
+            };
+            let distance = distance.min(lengths[index] * 0.5);
+            slid.insert(
+                index,
+                self.add_point(position + directions[index] * distance),
+            );
+        }
+
+        for index in 0..fan.face_count() {
+            let next = fan.next(index);
+            let attrs = &attributes[index];
+            let mut corners = Vec::new();
+            match (kinds[index], kinds[next]) {
+                (Cut::Bevel, Cut::Bevel) => {
+                    let bisector = directions[index] + directions[next];
+                    let half_angle = (bisector.norm() * 0.5).clamp(-1.0, 1.0).acos();
+                    let distance = (width / half_angle.sin().max(f32::EPSILON))
+                        .min(lengths[index].min(lengths[next]) * 0.5);
+                    let inside = position
+                        + bisector
+                            .try_normalize(f32::EPSILON)
+                            .unwrap_or_else(nalgebra::zero)
+                            * distance;
+                    corners.push(self.add_point(inside));
+                }
+                (Cut::Bevel, _) => corners.push(slid[&next]),
+                (_, Cut::Bevel) => corners.push(slid[&index]),
+                (current, following) => {
+                    if following == Cut::Slide {
+                        corners.push(slid[&next]);
+                    }
+                    if current == Cut::Keep || following == Cut::Keep {
+                        corners.push(point);
+                    } else if rounded_corners {
+                        let (a, b) = (
+                            self.point(slid[&next]).position(),
+                            self.point(slid[&index]).position(),
+                        );
+                        for step in 1..segments {
+                            let t = step as f32 / segments as f32;
+                            corners.push(self.add_point(profile_position(&a, &position, &b, t)));
+                        }
+                    }
+                    if current == Cut::Slide {
+                        corners.push(slid[&index]);
+                    }
+                }
+            }
+            let corners = corners.into_iter().map(|p| (p, attrs.clone())).collect();
+            bevel.cuts.insert(fan.edges[index].index(), corners);
+            bevel.faces.push(self.edge(fan.edges[index]).face().handle);
+        }
+
+        // Profiles across the beveled edges, from the corner of the face on
+        // the left to the one on the right.
+        let beveled: Vec<_> = (0..count).filter(|i| kinds[*i] == Cut::Bevel).collect();
+        for index in beveled.iter().cloned() {
+            let previous = (index + count - 1) % count;
+            let (left, left_attrs) = bevel.cuts[&fan.edges[index].index()]
+                .last()
+                .cloned()
+                .unwrap();
+            let (right, right_attrs) = bevel.cuts[&fan.edges[previous].index()][0].clone();
+            let (a, b) = (self.point(left).position(), self.point(right).position());
+            let mut profile = vec![(left, left_attrs.clone())];
+            for step in 1..segments {
+                let t = step as f32 / segments as f32;
+                let attrs = VertexAttributes::blend(&[(&left_attrs, 1.0 - t), (&right_attrs, t)]);
+                profile.push((
+                    self.add_point(profile_position(&a, &position, &b, t)),
+                    attrs,
+                ));
+            }
+            profile.push((right, right_attrs));
+            bevel.profiles.insert(fan.edges[index].index(), profile);
+        }
+
+        // With a single beveled edge and two others, the face opposite to it
+        // takes the profile instead of leaving a hole between them.
+        if let [index] = beveled.as_slice() {
+            if fan.closed && !kinds.contains(&Cut::Keep) {
+                let opposite = fan.next(*index);
+                let attrs = attributes[opposite].clone();
+                let profile = bevel.profiles[&fan.edges[*index].index()]
+                    .iter()
+                    .rev()
+                    .map(|(point, _)| (*point, attrs.clone()))
+                    .collect();
+                bevel.cuts.insert(fan.edges[opposite].index(), profile);
+            }
+        }
+
+        // The hole is left open on the boundary.
+        if !fan.closed {
+            return;
+        }
+        let mut hole = Vec::new();
+        for index in 0..fan.face_count() {
+            hole.extend(bevel.cuts[&fan.edges[index].index()].iter().rev().cloned());
+            let next = fan.next(index);
+            if kinds[next] == Cut::Bevel {
+                let profile = &bevel.profiles[&fan.edges[next].index()];
+                hole.extend(profile[1..profile.len() - 1].iter().rev().cloned());
+            }
+        }
+        for corners in simple_loops(hole) {
+            let (points, attributes) = corners.into_iter().unzip();
+            bevel.polygons.push(Polygon {
+                face: FaceHandle::default(),
+                points,
+                attributes,
+            });
+        }
+    }
+
+    /// Rebuilds the faces around the beveled points, adding `strips` and
+    /// the polygons filling the holes. Returns the new faces.
+    fn apply_bevel(&mut self, mut bevel: Bevel, strips: Vec<Polygon>) -> Vec<FaceHandle> {
+        let mut faces = bevel.faces.clone();
+        faces.sort_by_key(|face| face.index());
+        faces.dedup_by_key(|face| face.index());
+
+        let mut polygons = Vec::with_capacity(faces.len());
+        for face in faces.iter() {
+            let (mut points, mut attributes) = (Vec::new(), Vec::new());
+            for edge in self.face(*face).edges() {
+                match bevel.cuts.get(&edge.handle.index()) {
+                    Some(corners) => {
+                        for (point, attrs) in corners {
+                            points.push(*point);
+                            attributes.push(attrs.clone());
+                        }
+                    }
+                    None => {
+                        points.push(edge.vertex().point().handle);
+                        attributes.push(self.vertex_attributes(edge.vertex().handle));
+                    }
+                }
+            }
+            polygons.push(Polygon {
+                face: *face,
+                points,
+                attributes,
+            });
+        }
+        let rebuilt = polygons.len();
+        polygons.extend(strips);
+        polygons.append(&mut bevel.polygons);
+        self.replace_faces(&faces, &polygons)[rebuilt..].to_vec()
+    }
+
+    /// Bevels edges, replacing each of them by a strip of `segments` faces
+    /// with a rounded profile, `width` away from the edge on both sides.
+    ///
+    /// Next to a beveled edge the points slide along the other edges of
+    /// the adjacent faces, where two beveled edges meet in a face the new
+    /// corner is placed inside the face. Holes around points where several
+    /// beveled edges meet are filled with polygons, except on the boundary
+    /// where they are left open. Offsets are limited to half the length of
+    /// the edges they slide along. Boundary edges can't be beveled and are
+    /// skipped.
+    ///
+    /// Either half-edge of an edge selects it. Returns the new faces.
+    pub fn bevel_edges(
+        &mut self,
+        edges: &[HalfEdgeHandle],
+        width: f32,
+        segments: usize,
+    ) -> Vec<FaceHandle> {
+        let segments = segments.max(1);
+        let mut beveled = HashSet::new();
+        let mut halves = Vec::new();
+        for edge in edges {
+            let edge = self.edge(*edge);
+            if !edge.is_valid() || edge.is_boundary() {
+                log::warn!("Skipping bevel of edge {}.", edge.handle.index());
+                continue;
+            }
+            if beveled.insert(edge.handle.index()) {
+                beveled.insert(edge.adjacent().handle.index());
+                halves.push(edge.handle);
+            }
+        }
+
+        let mut bevel = Bevel::default();
+        let mut visited = HashSet::new();
+        let starts: Vec<_> = halves
+            .iter()
+            .flat_map(|edge| vec![*edge, self.edge(*edge).adjacent().handle])
+            .collect();
+        for start in starts {
+            if visited.contains(&start.index()) {
+                continue;
+            }
+            let fan = Fan::new(self, start);
+            visited.extend(fan.edges.iter().map(|edge| edge.index()));
+            let is_beveled = |index: usize| beveled.contains(&fan.edges[index].index());
+            let kinds: Vec<_> = (0..fan.edges.len())
+                .map(|index| {
+                    if is_beveled(index) {
+                        Cut::Bevel
+                    } else if fan.neighbors(index).into_iter().any(is_beveled) {
+                        Cut::Slide
+                    } else {
+                        Cut::Keep
+                    }
+                })
+                .collect();
+            self.cut_fan(&mut bevel, &fan, &kinds, width, segments);
+        }
+
+        let mut strips = Vec::new();
+        for edge in halves {
+            let twin = self.edge(edge).adjacent().handle;
+            let near = &bevel.profiles[&edge.index()];
+            let far: Corners = bevel.profiles[&twin.index()]
+                .iter()
+                .rev()
+                .cloned()
+                .collect();
+            for step in 0..segments {
+                let corners = [&far[step], &near[step], &near[step + 1], &far[step + 1]];
+                strips.push(Polygon {
+                    face: FaceHandle::default(),
+                    points: corners.iter().map(|(point, _)| *point).collect(),
+                    attributes: corners.iter().map(|(_, attrs)| attrs.clone()).collect(),
+                });
+            }
+        }
+        self.apply_bevel(bevel, strips)
+    }
+
+    /// Bevels the points of the vertices, cutting off the corners around
+    /// them `width` along each edge. With more than one segment the cuts
+    /// through the faces are rounded.
+    ///
+    /// Offsets are limited to half the length of the edges. Points on the
+    /// boundary just lose their corner. Returns the polygons replacing the
+    /// points.
+    pub fn bevel_vertices(
+        &mut self,
+        vertices: &[VertexHandle],
+        width: f32,
+        segments: usize,
+    ) -> Vec<FaceHandle> {
+        let segments = segments.max(1);
+        let mut bevel = Bevel::default();
+        let mut visited = HashSet::new();
+        for vert in vertices {
+            let start = self.vertex(*vert).edge().handle;
+            if !self.vertex(*vert).is_valid() || visited.contains(&start.index()) {
+                continue;
+            }
+            let fan = Fan::new(self, start);
+            visited.extend(fan.edges.iter().map(|edge| edge.index()));
+            let kinds = vec![Cut::Slide; fan.edges.len()];
+            self.cut_fan(&mut bevel, &fan, &kinds, width, segments);
+        }
+        self.apply_bevel(bevel, Vec::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::*;
+    use crate::*;
+
+    /// The edges of the cube along the Y axis at `x` and `z`.
+    fn vertical_edge(mesh: &Mesh, x: f32, z: f32) -> HalfEdgeHandle {
+        mesh.edges()
+            .find(|edge| {
+                let (p0, p1) = edge.full_edge().points();
+                let (p0, p1) = (p0.position(), p1.position());
+                p0.x == x && p1.x == x && p0.z == z && p1.z == z
+            })
+            .unwrap()
+            .handle
+    }
+
+    #[test]
+    fn can_bevel_edge_of_cube() {
+        let _ = env_logger::try_init();
+        let mut mesh = Mesh::unit_cube();
+        let edge = vertical_edge(&mesh, 0.0, 0.0);
+        let faces = mesh.bevel_edges(&[edge], 0.25, 1);
+        assert!(mesh.is_valid());
+        assert_eq!(faces.len(), 1);
+        assert_eq!(mesh.face_count(), 7);
+        assert_eq!(mesh.point_count(), 10);
+        assert_eq!(boundary_edge_count(&mesh), 0);
+        assert_eq!(euler_characteristic(&mesh), 2);
+        let pentagons = mesh
+            .faces()
+            .filter(|face| face.vertices().count() == 5)
+            .count();
+        assert_eq!(pentagons, 2);
+        for vert in mesh.face(faces[0]).vertices() {
+            let position = vert.point().position();
+            assert!((position.x + position.z - 0.25).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn can_bevel_edge_with_segments() {
+        let _ = env_logger::try_init();
+        let mut mesh = Mesh::unit_cube();
+        let edge = vertical_edge(&mesh, 1.0, 1.0);
+        let faces = mesh.bevel_edges(&[edge], 0.25, 3);
+        assert!(mesh.is_valid());
+        assert_eq!(faces.len(), 3);
+        assert_eq!(mesh.face_count(), 9);
+        assert_eq!(mesh.point_count(), 14);
+        assert_eq!(boundary_edge_count(&mesh), 0);
+        assert_eq!(euler_characteristic(&mesh), 2);
+        // The profile bulges out towards the old edge.
+        for face in faces {
+            for vert in mesh.face(face).vertices() {
+                let position = vert.point().position();
+                assert!(position.x + position.z >= 1.75 - 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn can_bevel_all_edges_of_cube() {
+        let _ = env_logger::try_init();
+        let mut mesh = Mesh::unit_cube();
+        let edges: Vec<_> = mesh
+            .full_edges()
+            .map(|edge| edge.half_edge().handle)
+            .collect();
+        let faces = mesh.bevel_edges(&edges, 0.1, 1);
+        assert!(mesh.is_valid());
+        assert_eq!(faces.len(), 20);
+        assert_eq!(mesh.face_count(), 26);
+        assert_eq!(mesh.point_count(), 24);
+        assert_eq!(boundary_edge_count(&mesh), 0);
+        assert_eq!(euler_characteristic(&mesh), 2);
+        let triangles = faces
+            .iter()
+            .filter(|face| mesh.face(**face).vertices().count() == 3)
+            .count();
+        assert_eq!(triangles, 8);
+
+        let mut mesh = Mesh::unit_cube();
+        let edges: Vec<_> = mesh
+            .full_edges()
+            .map(|edge| edge.half_edge().handle)
+            .collect();
+        let faces = mesh.bevel_edges(&edges, 0.1, 3);
+        assert!(mesh.is_valid());
+        assert_eq!(faces.len(), 44);
+        assert_eq!(boundary_edge_count(&mesh), 0);
+        assert_eq!(euler_characteristic(&mesh), 2);
+    }
+
+    #[test]
+    fn can_bevel_edges_of_open_mesh() {
+        let _ = env_logger::try_init();
+        let mut mesh = Mesh::grid(1.0, 1.0, 2, 2);
+        // The edge from the center to the middle of a side.
+        let edge = mesh
+            .edges()
+            .find(|edge| {
+                let (p0, p1) = edge.full_edge().points();
+                p0.position().coords.norm() == 0.0 && p1.position().x == 0.5
+                    || p1.position().coords.norm() == 0.0 && p0.position().x == 0.5
+            })
+            .unwrap()
+            .handle;
+        let faces = mesh.bevel_edges(&[edge], 0.1, 2);
+        assert!(mesh.is_valid());
+        assert_eq!(faces.len(), 3);
+        assert_eq!(mesh.face_count(), 7);
+        assert_eq!(euler_characteristic(&mesh), 1);
+        for vert in mesh.vertices() {
+            assert_eq!(vert.point().position().y, 0.0);
+        }
+    }
+
+    #[test]
+    fn can_bevel_vertices() {
+        let _ = env_logger::try_init();
+        let mut mesh = Mesh::unit_cube();
+        let vertex = mesh
+            .vertices()
+            .find(|vert| vert.point().position().coords.norm() == 0.0)
+            .unwrap()
+            .handle;
+        let faces = mesh.bevel_vertices(&[vertex], 0.25, 1);
+        assert!(mesh.is_valid());
+        assert_eq!(faces.len(), 1);
+        assert_eq!(mesh.face_count(), 7);
+        assert_eq!(mesh.point_count(), 10);
+        assert_eq!(euler_characteristic(&mesh), 2);
+        for vert in mesh.face(faces[0]).vertices() {
+            let position = vert.point().position();
+            assert!((position.x + position.y + position.z - 0.25).abs() < 1e-6);
+        }
+
+        let mut mesh = Mesh::unit_cube();
+        let vertices: Vec<_> = mesh.vertices().map(|vert| vert.handle).collect();
+        let faces = mesh.bevel_vertices(&vertices, 0.25, 2);
+        assert!(mesh.is_valid());
+        assert_eq!(faces.len(), 8);
+        assert_eq!(mesh.face_count(), 14);
+        assert_eq!(boundary_edge_count(&mesh), 0);
+        assert_eq!(euler_characteristic(&mesh), 2);
+        for face in faces {
+            assert_eq!(mesh.face(face).vertices().count(), 6);
+        }
+    }
+}
@@ -0,0 +1,296 @@
+//! Bridging of boundary loops and faces.
+
+use std::collections::HashSet;
+
+use crate::data::VertexAttributes;
+use crate::handles::{FaceHandle, HalfEdgeHandle, PointHandle};
+use crate::mesh::Mesh;
+use crate::proxy::*;
+use crate::traits::*;
+
+/// One face of the strip, consuming an edge of the first loop, of the
+/// second loop, or of both.
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Step {
+    First,
+    Second,
+    Both,
+}
+
+/// Spreads the triangles needed for loops of `n` and `m` edges evenly
+/// between the quads.
+fn steps(n: usize, m: usize) -> Vec<Step> {
+    let (long, short, single) = if n >= m {
+        (n, m, Step::First)
+    } else {
+        (m, n, Step::Second)
+    };
+    let extra = long - short;
+    (0..long)
+        .map(|index| {
+            if (index + 1) * extra / long > index * extra / long {
+                single
+            } else {
+                Step::Both
+            }
+        })
+        .collect()
+}
+
+impl Mesh {
+    fn loop_attributes(&self, edge: HalfEdgeHandle) -> VertexAttributes {
+        self.edge(edge)
+            .vertex()
+            .data()
+            .map(|data| data.attrs.clone())
+            .unwrap_or_default()
+    }
+
+    /// Connects two boundary loops with a strip of faces.
+    ///
+    /// The second loop is walked against the first, starting at the point
+    /// which lines the loops up best. Loops of equal length are joined by
+    /// quads, otherwise triangles are spread evenly between the quads.
+    fn bridge_loops(
+        &mut self,
+        first: &[HalfEdgeHandle],
+        second: &[HalfEdgeHandle],
+    ) -> Vec<FaceHandle> {
+        let (n, m) = (first.len(), second.len());
+        if n == 0 || m == 0 {
+            log::warn!("Can't bridge an empty loop.");
+            return Vec::new();
+        }
+        let first_edges: HashSet<_> = first.iter().map(|edge| edge.index()).collect();
+        if second
+            .iter()
+            .any(|edge| first_edges.contains(&edge.index()))
+        {
+            log::warn!("Can't bridge a loop with itself.");
+            return Vec::new();
+        }
+
+        let start = |edge: &HalfEdgeHandle| self.edge(*edge).vertex().point().handle;
+        let u: Vec<PointHandle> = first.iter().map(start).collect();
+        let w: Vec<PointHandle> = second.iter().map(start).collect();
+
+        // The k-th point of the second loop, walked backwards from `offset`.
+        let second_index = |offset: usize, k: usize| (offset + m - k % m) % m;
+        let offset = (0..m)
+            .map(|offset| {
+                let cost: f32 = (0..n)
+                    .map(|i| {
+                        let k = (i * m + n / 2) / n;
+                        let a = self.point(u[i]).position();
+                        let b = self.point(w[second_index(offset, k)]).position();
+                        (b - a).norm_squared()
+                    })
+                    .sum();
+                (offset, cost)
+            })
+            .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
+            .map(|(offset, _)| offset)
+            .unwrap_or(0);
+
+        let steps = steps(n, m);
+        let mut states = Vec::with_capacity(steps.len());
+        let (mut i, mut k) = (0, 0);
+        for step in steps.iter() {
+            states.push((i, k));
+            match step {
+                Step::First => i += 1,
+                Step::Second => k += 1,
+                Step::Both => {
+                    i += 1;
+                    k += 1;
+                }
+            }
+        }
+
+        // Edges between the two loops, leading from the first to the second.
+        let mut connectors = Vec::with_capacity(states.len());
+        for (i, k) in states.iter() {
+            let (a, b) = (first[i % n], second[second_index(offset, *k)]);
+            let (up, down) = self.make_edge((u[i % n], w[second_index(offset, *k)]));
+            for (edge, attrs) in [(up, a), (down, b)].iter() {
+                let attrs = self.loop_attributes(*attrs);
+                if let Some(vert) = self.edge(*edge).vertex().element() {
+                    vert.data_mut().attrs = attrs;
+                }
+            }
+            connectors.push((up, down));
+        }
+
+        let mut faces = Vec::with_capacity(steps.len());
+        for (index, step) in steps.iter().enumerate() {
+            let (i, k) = states[index];
+            let (up, _) = connectors[(index + 1) % connectors.len()];
+            let (_, down) = connectors[index];
+            let a = first[i % n];
+            let b = second[second_index(offset + m - 1, k)];
+            let edges = match step {
+                Step::First => vec![a, up, down],
+                Step::Second => vec![up, b, down],
+                Step::Both => vec![a, up, b, down],
+            };
+            faces.push(self.add_face(edges.as_slice()));
+        }
+        faces
+    }
+}
+
+impl Bridge<(HalfEdgeHandle, HalfEdgeHandle)> for Mesh {
+    /// Bridges the boundary loops containing the two half-edges.
+    fn bridge(&mut self, (e0, e1): (HalfEdgeHandle, HalfEdgeHandle)) -> Vec<FaceHandle> {
+        log::trace!(
+            "- Bridge<(HalfEdgeHandle({}), HalfEdgeHandle({}))>",
+            e0.index(),
+            e1.index()
+        );
+        let (first, second) = (self.boundary_loop(e0), self.boundary_loop(e1));
+        self.bridge_loops(&first, &second)
+    }
+}
+
+impl Bridge<(FaceHandle, FaceHandle)> for Mesh {
+    /// Removes the two faces and bridges the holes left behind.
+    fn bridge(&mut self, (f0, f1): (FaceHandle, FaceHandle)) -> Vec<FaceHandle> {
+        log::trace!(
+            "- Bridge<(FaceHandle({}), FaceHandle({}))>",
+            f0.index(),
+            f1.index()
+        );
+        if !self.face(f0).is_valid() || !self.face(f1).is_valid() || f0.index() == f1.index() {
+            log::warn!("Can only bridge two distinct valid faces.");
+            return Vec::new();
+        }
+        let first = self.open_face(f0);
+        let second = self.open_face(f1);
+        self.bridge_loops(&first, &second)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::*;
+    use crate::*;
+
+    /// Two open cylinders of `n` and `m` segments, stacked above each other.
+    fn tubes(n: usize, m: usize) -> (Mesh, HalfEdgeHandle, HalfEdgeHandle) {
+        let mut positions = Vec::new();
+        let mut polygons = Vec::new();
+        for (segments, y) in [(n, 0.0), (m, 2.0)].iter() {
+            let base = positions.len();
+            for ring in 0..2 {
+                for index in 0..*segments {
+                    let angle = index as f32 * std::f32::consts::PI * 2.0 / *segments as f32;
+                    let y = y + ring as f32 - 0.5;
+                    positions.push(Position::new(angle.cos(), y, angle.sin()));
+                }
+            }
+            for index in 0..*segments {
+                let next = (index + 1) % segments;
+                polygons.push(vec![
+                    base + index,
+                    base + segments + index,
+                    base + segments + next,
+                    base + next,
+                ]);
+            }
+        }
+        let mesh = Mesh::from_polygons(&positions, &polygons);
+        let mut upper = None;
+        let mut lower = None;
+        for edge in mesh.edges().filter(|edge| !edge.face().is_valid()) {
+            let y = edge.vertex().point().position().y;
+            if y == 0.5 {
+                upper = Some(edge.handle);
+            } else if y == 1.5 {
+                lower = Some(edge.handle);
+            }
+        }
+        (mesh, upper.unwrap(), lower.unwrap())
+    }
+
+    #[test]
+    fn can_bridge_loops_of_equal_length() {
+        let _ = env_logger::try_init();
+        let (mut mesh, upper, lower) = tubes(8, 8);
+        assert_eq!(boundary_edge_count(&mesh), 32);
+        let faces = mesh.bridge((upper, lower));
+        assert!(mesh.is_valid());
+        assert_eq!(faces.len(), 8);
+        assert_eq!(mesh.face_count(), 24);
+        assert_eq!(boundary_edge_count(&mesh), 16);
+        for face in faces {
+            let face = mesh.face(face);
+            assert_eq!(face.vertices().count(), 4);
+            // The loops are lined up, so no face is twisted around the tube.
+            let positions: Vec<_> = face.vertices().map(|v| v.point().position()).collect();
+            for position in positions.iter() {
+                assert!((position - positions[0]).norm() < 1.5);
+            }
+        }
+    }
+
+    #[test]
+    fn can_bridge_loops_of_different_length() {
+        let _ = env_logger::try_init();
+        let (mut mesh, upper, lower) = tubes(8, 5);
+        let faces = mesh.bridge((upper, lower));
+        assert!(mesh.is_valid());
+        assert_eq!(faces.len(), 8);
+        let triangles = faces
+            .iter()
+            .filter(|face| mesh.face(**face).vertices().count() == 3)
+            .count();
+        assert_eq!(triangles, 3);
+        assert_eq!(boundary_edge_count(&mesh), 13);
+    }
+
+    #[test]
+    fn can_bridge_faces() {
+        let _ = env_logger::try_init();
+        let cube = Mesh::unit_cube();
+        let mut corners = Vec::new();
+        for vert in cube.vertices() {
+            let position = vert.point().position();
+            if !corners.contains(&position) {
+                corners.push(position);
+            }
+        }
+        let polygons: Vec<Vec<usize>> = cube
+            .faces()
+            .map(|face| {
+                face.vertices()
+                    .map(|vert| {
+                        let position = vert.point().position();
+                        corners.iter().position(|p| *p == position).unwrap()
+                    })
+                    .collect()
+            })
+            .collect();
+        let mut positions = corners.clone();
+        positions.extend(corners.iter().map(|p| Position::new(p.x, p.y + 2.0, p.z)));
+        let mut both = polygons.clone();
+        both.extend(
+            polygons
+                .iter()
+                .map(|polygon| polygon.iter().map(|index| index + 8).collect()),
+        );
+        let mut mesh = Mesh::from_polygons(&positions, &both);
+        let find = |mesh: &Mesh, y: f32| {
+            mesh.faces()
+                .find(|face| face.vertices().all(|v| v.point().position().y == y))
+                .unwrap()
+                .handle
+        };
+        let (top, bottom) = (find(&mesh, 1.0), find(&mesh, 2.0));
+
+        let faces = mesh.bridge((top, bottom));
+        assert!(mesh.is_valid());
+        assert_eq!(faces.len(), 4);
+        assert_eq!(mesh.face_count(), 14);
+        assert_eq!(boundary_edge_count(&mesh), 0);
+    }
+}
@@ -0,0 +1,276 @@
+//! Flattening of a mesh into buffers suitable for rendering.
+
+use std::collections::HashMap;
+
+use crate::data::{EdgeFilter, IndexBuffer, LineList, Normal, Shading, TriangleList};
+use crate::handles::PointHandle;
+use crate::mesh::Mesh;
+use crate::proxy::*;
+use nalgebra as na;
+
+/// An additional per vertex attribute appended to each vertex of a `TriangleList`.
+pub struct CustomAttribute<'a> {
+    /// Number of `f32` components written for every vertex.
+    pub components: usize,
+    /// Fills the components for the given vertex.
+    pub fill: &'a dyn Fn(&VertexProxy, &mut [f32]),
+}
+
+fn normalize_or_zero(normal: Normal) -> Normal {
+    normal.try_normalize(f32::EPSILON).unwrap_or_else(na::zero)
+}
+
+fn push_vertex(
+    vertices: &mut Vec<f32>,
+    vert: &VertexProxy,
+    normal: &Normal,
+    attributes: &[CustomAttribute],
+) {
+    vertices.extend_from_slice(vert.point().position().coords.as_slice());
+    vertices.extend_from_slice(normal.as_slice());
+    match vert.data() {
+        Some(data) => vertices.extend_from_slice(data.attrs.color.as_slice()),
+        None => vertices.extend_from_slice(&[1.0, 1.0, 1.0, 1.0]),
+    }
+    for attribute in attributes {
+        let offset = vertices.len();
+        vertices.resize(offset + attribute.components, 0.0);
+        (attribute.fill)(vert, &mut vertices[offset..]);
+    }
+}
+
+impl Mesh {
+    /// Flattens all faces of the mesh into an interleaved `TriangleList`.
+    pub fn triangle_list(&self, shading: Shading) -> TriangleList {
+        self.triangle_list_with_attributes(shading, &[])
+    }
+
+    /// Flattens all faces of the mesh into an interleaved `TriangleList`
+    /// with the given custom attributes appended to every vertex.
+    ///
+    /// In smooth mode render vertices are shared per `Point`, and the color
+    /// and custom attributes are taken from the first `Vertex` found at it.
+    pub fn triangle_list_with_attributes(
+        &self,
+        shading: Shading,
+        attributes: &[CustomAttribute],
+    ) -> TriangleList {
+        let stride =
+            TriangleList::CUSTOM_OFFSET + attributes.iter().map(|a| a.components).sum::<usize>();
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+        let mut vertex_count = 0;
+
+        match shading {
+            Shading::Flat => {
+                for face in self.faces() {
+                    let normal = face.normal();
+                    let base = vertex_count;
+                    let mut offsets = HashMap::new();
+                    for vert in face.vertices() {
+                        push_vertex(&mut vertices, &vert, &normal, attributes);
+                        offsets.insert(vert.handle, vertex_count);
+                        vertex_count += 1;
+                    }
+                    for (v0, v1, v2) in face.triangles() {
+                        for vert in [v0, v1, v2].iter() {
+                            let offset = offsets.get(&vert.handle).cloned().unwrap_or(base);
+                            indices.push(offset as u32);
+                        }
+                    }
+                }
+            }
+            Shading::Smooth => {
+                let mut normals: HashMap<PointHandle, Normal> = HashMap::new();
+                for face in self.faces() {
+                    let area_vector = face.area_vector();
+                    for vert in face.vertices() {
+                        *normals.entry(vert.point().handle).or_insert_with(na::zero) += area_vector;
+                    }
+                }
+
+                let mut offsets: HashMap<PointHandle, usize> = HashMap::new();
+                for face in self.faces() {
+                    for (v0, v1, v2) in face.triangles() {
+                        for vert in [v0, v1, v2].iter() {
+                            let point = vert.point().handle;
+                            let offset = *offsets.entry(point).or_insert_with(|| {
+                                let normal = normalize_or_zero(normals[&point]);
+                                push_vertex(&mut vertices, vert, &normal, attributes);
+                                vertex_count += 1;
+                                vertex_count - 1
+                            });
+                            indices.push(offset as u32);
+                        }
+                    }
+                }
+            }
+        }
+
+        TriangleList {
+            stride,
+            vertices,
+            indices: IndexBuffer::new(indices, vertex_count),
+        }
+    }
+
+    /// Collects the edges of the mesh matching `filter` as line segments.
+    ///
+    /// Each full edge is emitted once.
+    pub fn line_list(&self, filter: EdgeFilter) -> LineList {
+        let mut positions = Vec::new();
+        let mut indices = Vec::new();
+        let mut offsets: HashMap<PointHandle, usize> = HashMap::new();
+
+        for edge in self.full_edges() {
+            let keep = match filter {
+                EdgeFilter::All => true,
+                EdgeFilter::Boundary => edge.is_boundary(),
+                EdgeFilter::Feature(threshold) => {
+                    !edge.is_boundary() && {
+                        let (f0, f1) = edge.faces();
+                        let n0 = f0.normal();
+                        let n1 = f1.normal();
+                        n0.dot(&n1).clamp(-1.0, 1.0).acos() > threshold
+                    }
+                }
+            };
+            if !keep {
+                continue;
+            }
+            let (p0, p1) = edge.points();
+            for point in [p0, p1].iter() {
+                let offset = *offsets.entry(point.handle).or_insert_with(|| {
+                    positions.extend_from_slice(point.position().coords.as_slice());
+                    positions.len() / 3 - 1
+                });
+                indices.push(offset as u32);
+            }
+        }
+
+        let vertex_count = positions.len() / 3;
+        LineList {
+            positions,
+            indices: IndexBuffer::new(indices, vertex_count),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    fn build_quad_pair(mesh: &mut Mesh) -> (FaceHandle, FaceHandle) {
+        let p0 = mesh.add(Point::from_position(0.0, 0.0, 0.0));
+        let p1 = mesh.add(Point::from_position(1.0, 0.0, 0.0));
+        let p2 = mesh.add(Point::from_position(1.0, 1.0, 0.0));
+        let p3 = mesh.add(Point::from_position(0.0, 1.0, 0.0));
+        let p4 = mesh.add(Point::from_position(2.0, 0.0, 0.0));
+        let p5 = mesh.add(Point::from_position(2.0, 1.0, 0.0));
+
+        let f0 = mesh.add_face([p0, p1, p2, p3].as_ref());
+        let shared_edge = mesh.face(f0).root_edge().next().adjacent().handle;
+        let f1 = mesh.add_face((shared_edge, [p4, p5].as_ref()));
+        (f0, f1)
+    }
+
+    #[test]
+    fn flat_shading_duplicates_vertices_per_face() {
+        let _ = env_logger::try_init();
+        let mut mesh = Mesh::default();
+        let _ = build_quad_pair(&mut mesh);
+
+        let list = mesh.triangle_list(Shading::Flat);
+        assert_eq!(list.stride, 10);
+        assert_eq!(list.vertex_count(), 8);
+        assert_eq!(list.triangle_count(), 4);
+        match list.indices {
+            IndexBuffer::U16(_) => {}
+            IndexBuffer::U32(_) => panic!("Expected 16 bit indices"),
+        }
+        for index in 0..list.vertex_count() {
+            let vertex = list.vertex(index);
+            let normal = &vertex[TriangleList::NORMAL_OFFSET..TriangleList::COLOR_OFFSET];
+            assert_eq!(normal, &[0.0, 0.0, 1.0]);
+        }
+    }
+
+    #[test]
+    fn smooth_shading_shares_vertices_per_point() {
+        let _ = env_logger::try_init();
+        let mut mesh = Mesh::default();
+        let _ = build_quad_pair(&mut mesh);
+
+        let list = mesh.triangle_list(Shading::Smooth);
+        assert_eq!(list.vertex_count(), 6);
+        assert_eq!(list.triangle_count(), 4);
+        assert!(list
+            .indices
+            .to_u32()
+            .iter()
+            .all(|i| (*i as usize) < list.vertex_count()));
+    }
+
+    #[test]
+    fn custom_attributes_are_appended() {
+        let _ = env_logger::try_init();
+        let mut mesh = Mesh::default();
+        let _ = build_quad_pair(&mut mesh);
+
+        let fill = |vert: &VertexProxy, values: &mut [f32]| {
+            let position = vert.point().position();
+            values[0] = position.x;
+            values[1] = position.y;
+        };
+        let attributes = [CustomAttribute {
+            components: 2,
+            fill: &fill,
+        }];
+        let list = mesh.triangle_list_with_attributes(Shading::Flat, &attributes);
+        assert_eq!(list.stride, 12);
+        for index in 0..list.vertex_count() {
+            let vertex = list.vertex(index);
+            assert_eq!(vertex[TriangleList::CUSTOM_OFFSET], vertex[0]);
+            assert_eq!(vertex[TriangleList::CUSTOM_OFFSET + 1], vertex[1]);
+        }
+    }
+
+    #[test]
+    fn line_list_emits_each_full_edge_once() {
+        let _ = env_logger::try_init();
+        let mut mesh = Mesh::default();
+        let _ = build_quad_pair(&mut mesh);
+
+        let lines = mesh.line_list(EdgeFilter::All);
+        assert_eq!(lines.line_count(), 7);
+        assert_eq!(lines.vertex_count(), 6);
+
+        let lines = mesh.line_list(EdgeFilter::Boundary);
+        assert_eq!(lines.line_count(), 6);
+
+        let lines = mesh.line_list(EdgeFilter::Feature(0.1));
+        assert_eq!(lines.line_count(), 0);
+    }
+
+    #[test]
+    fn line_list_finds_feature_edges() {
+        let _ = env_logger::try_init();
+        let mut mesh = Mesh::default();
+
+        let p0 = mesh.add(Point::from_position(0.0, 0.0, 0.0));
+        let p1 = mesh.add(Point::from_position(1.0, 0.0, 0.0));
+        let p2 = mesh.add(Point::from_position(0.0, 1.0, 0.0));
+        let p3 = mesh.add(Point::from_position(0.0, 0.0, -1.0));
+
+        let f0 = mesh.add_face([p0, p1, p2].as_ref());
+        let shared_edge = mesh.face(f0).root_edge().adjacent().handle;
+        let _ = mesh.add_face((shared_edge, [p3].as_ref()));
+
+        let lines = mesh.line_list(EdgeFilter::Feature(std::f32::consts::FRAC_PI_4));
+        assert_eq!(lines.line_count(), 1);
+        assert_eq!(lines.vertex_count(), 2);
+
+        let lines = mesh.line_list(EdgeFilter::Feature(std::f32::consts::FRAC_PI_2 + 0.1));
+        assert_eq!(lines.line_count(), 0);
+    }
+}
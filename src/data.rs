@@ -11,8 +11,10 @@ pub type Color = na::Vector4<f32>;
 
 #[derive(Debug, Clone)]
 pub struct VertexAttributes {
-    normal: Normal,
-    color: Color,
+    /// Shading normal of the vertex.
+    pub normal: Normal,
+    /// Vertex color.
+    pub color: Color,
 }
 
 impl Default for VertexAttributes {
@@ -24,6 +26,25 @@ impl Default for VertexAttributes {
     }
 }
 
+impl VertexAttributes {
+    /// Weighted combination of several sets of attributes.
+    ///
+    /// The weights are expected to sum up to one. The resulting normal
+    /// is normalized unless it ends up being zero.
+    pub fn blend(weighted: &[(&VertexAttributes, f32)]) -> Self {
+        let mut normal: Normal = na::zero();
+        let mut color: Color = na::zero();
+        for (attrs, weight) in weighted {
+            normal += attrs.normal * *weight;
+            color += attrs.color * *weight;
+        }
+        VertexAttributes {
+            normal: normal.try_normalize(f32::EPSILON).unwrap_or(normal),
+            color,
+        }
+    }
+}
+
 /// Whether or not a cell is current or 'removed'
 #[derive(Debug, Copy, Clone, PartialOrd, PartialEq)]
 pub enum ElementStatus {
@@ -43,6 +64,8 @@ pub struct HalfEdgeData {
     pub face: FaceHandle,
     /// The Handle of the Vertex for this edge.
     pub vertex: VertexHandle,
+    /// Sharpness of the edge during subdivision, zero for smooth edges.
+    pub crease: f32,
 }
 impl ElementData for HalfEdgeData {}
 
@@ -93,8 +116,211 @@ impl PointData {
 
 impl ElementData for PointData {}
 
-#[allow(unused)]
-struct TriangleList {
-    indices: Vec<u16>,
-    vertices: Vec<f32>,
+/// How normals are generated when flattening a mesh into a `TriangleList`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Shading {
+    /// Every face gets its own vertices which all carry the face normal.
+    Flat,
+    /// Vertices are shared between faces at the same point and carry
+    /// the area weighted average of the adjacent face normals.
+    Smooth,
+}
+
+/// Index storage for render buffers.
+///
+/// 16 bit indices are used whenever the vertex count allows it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum IndexBuffer {
+    U16(Vec<u16>),
+    U32(Vec<u32>),
+}
+
+impl IndexBuffer {
+    /// Picks the smallest index type able to address `vertex_count` vertices.
+    pub fn new(indices: Vec<u32>, vertex_count: usize) -> Self {
+        if vertex_count <= (u16::MAX as usize) + 1 {
+            IndexBuffer::U16(indices.into_iter().map(|i| i as u16).collect())
+        } else {
+            IndexBuffer::U32(indices)
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            IndexBuffer::U16(indices) => indices.len(),
+            IndexBuffer::U32(indices) => indices.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns a copy of the indices widened to `u32`.
+    pub fn to_u32(&self) -> Vec<u32> {
+        match self {
+            IndexBuffer::U16(indices) => indices.iter().map(|i| u32::from(*i)).collect(),
+            IndexBuffer::U32(indices) => indices.clone(),
+        }
+    }
+}
+
+/// Interleaved vertex data and triangle indices ready to hand to a
+/// graphics api.
+///
+/// Each vertex is laid out as `[position(3), normal(3), color(4), custom..]`.
+#[derive(Debug, Clone)]
+pub struct TriangleList {
+    /// Number of `f32` values per vertex.
+    pub stride: usize,
+    pub vertices: Vec<f32>,
+    pub indices: IndexBuffer,
+}
+
+impl TriangleList {
+    pub const POSITION_OFFSET: usize = 0;
+    pub const NORMAL_OFFSET: usize = 3;
+    pub const COLOR_OFFSET: usize = 6;
+    /// Offset of the first custom attribute component.
+    pub const CUSTOM_OFFSET: usize = 10;
+
+    pub fn vertex_count(&self) -> usize {
+        self.vertices.len().checked_div(self.stride).unwrap_or(0)
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    /// Returns the interleaved values of the vertex at `index`.
+    pub fn vertex(&self, index: usize) -> &[f32] {
+        let offset = index * self.stride;
+        &self.vertices[offset..offset + self.stride]
+    }
+}
+
+/// Axis-aligned box enclosing a set of positions.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BoundingBox {
+    pub min: Position,
+    pub max: Position,
+}
+
+impl BoundingBox {
+    pub fn center(&self) -> Position {
+        na::center(&self.min, &self.max)
+    }
+
+    pub fn size(&self) -> Normal {
+        self.max - self.min
+    }
+}
+
+/// Whether a set of faces is extruded or inset as a whole or face by face.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ExtrudeMode {
+    /// Neighboring faces stay connected and only the outline of the
+    /// region gets side faces.
+    Region,
+    /// Every face gets its own side faces.
+    Individual,
+}
+
+/// How faces are split up by `Mesh::triangulate`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Triangulation {
+    /// All triangles share the root corner of the face. Only suited for
+    /// convex faces.
+    Fan,
+    /// Cuts off convex corners not overlapping the rest of the face.
+    EarClipping,
+    /// Like `EarClipping` but cuts off the corner with the shortest
+    /// diagonal first.
+    ShortestDiagonal,
+}
+
+/// What happens to the faces around a vertex removed by `Mesh::remove_vertex`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum VertexRemoval {
+    /// The incident edges are removed and the surrounding faces merged
+    /// into one.
+    Dissolve,
+    /// The incident faces are removed, leaving a hole.
+    Delete,
+}
+
+/// How the neighbors of a point are weighted when averaging them.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Weighting {
+    /// Every neighbor counts the same.
+    Uniform,
+    /// Neighbors are weighted by the cotangents of the angles opposite
+    /// to the edge leading to them. Meant for triangle meshes, other faces
+    /// use the corner preceding the edge.
+    Cotangent,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct SmoothingOptions {
+    pub weighting: Weighting,
+    /// Number of smoothing steps, or of λ/μ step pairs for Taubin smoothing.
+    pub iterations: usize,
+    /// Keeps boundary points in place. Otherwise they are only smoothed
+    /// along the boundary.
+    pub pin_boundary: bool,
+    /// Points carrying this tag are kept in place.
+    pub pinned_tag: Option<Tag>,
+}
+
+impl Default for SmoothingOptions {
+    fn default() -> Self {
+        SmoothingOptions {
+            weighting: Weighting::Uniform,
+            iterations: 1,
+            pin_boundary: true,
+            pinned_tag: None,
+        }
+    }
+}
+
+/// How `Mesh::fill_hole` closes a boundary loop.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum HoleFilling {
+    /// A single face using all edges of the loop.
+    Polygon,
+    /// Triangles with the smallest total area.
+    MinimumArea,
+    /// Triangles minimizing the largest dihedral angle between neighboring
+    /// faces, using the total area to break ties.
+    MinimumDihedral,
+}
+
+/// Which edges are emitted by `Mesh::line_list`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum EdgeFilter {
+    /// Every edge of the mesh.
+    All,
+    /// Only edges with a face on just one side.
+    Boundary,
+    /// Only edges between two faces whose normals differ by more
+    /// than the given angle in radians.
+    Feature(f32),
+}
+
+/// Line segments as pairs of indices into a flat position buffer.
+#[derive(Debug, Clone)]
+pub struct LineList {
+    /// Three `f32` values per vertex.
+    pub positions: Vec<f32>,
+    pub indices: IndexBuffer,
+}
+
+impl LineList {
+    pub fn vertex_count(&self) -> usize {
+        self.positions.len() / 3
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.indices.len() / 2
+    }
 }
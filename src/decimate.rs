@@ -0,0 +1,417 @@
+//! Mesh simplification driven by quadric error metrics.
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+use crate::data::Position;
+use crate::handles::{HalfEdgeHandle, PointHandle};
+use crate::mesh::Mesh;
+use crate::proxy::*;
+use crate::traits::*;
+use nalgebra as na;
+
+type Quadric = na::Matrix4<f64>;
+
+/// Weight of the planes keeping boundary edges in place, relative to
+/// the area weighted face planes.
+const BOUNDARY_WEIGHT: f64 = 1000.0;
+
+fn to_f64(position: &Position) -> na::Vector3<f64> {
+    na::Vector3::new(
+        f64::from(position.x),
+        f64::from(position.y),
+        f64::from(position.z),
+    )
+}
+
+fn plane_quadric(normal: &na::Vector3<f64>, point: &na::Vector3<f64>, weight: f64) -> Quadric {
+    let plane = na::Vector4::new(normal.x, normal.y, normal.z, -normal.dot(point));
+    plane * plane.transpose() * weight
+}
+
+/// Squared distance of `position` to the planes summed up in the quadric.
+fn quadric_error(quadric: &Quadric, position: &na::Vector3<f64>) -> f64 {
+    let v = na::Vector4::new(position.x, position.y, position.z, 1.0);
+    (v.transpose() * quadric * v)[(0, 0)].max(0.0)
+}
+
+/// Corner positions of a face, with `moved` points replaced by `position`.
+///
+/// Consecutive corners ending up at the same point are merged.
+fn corner_positions(
+    face: &FaceProxy,
+    moved: &[PointHandle],
+    position: &Position,
+) -> Vec<na::Vector3<f64>> {
+    let mut positions = Vec::new();
+    let mut last_moved = false;
+    for vert in face.vertices() {
+        let point = vert.point();
+        if moved.contains(&point.handle) {
+            if !last_moved {
+                positions.push(to_f64(position));
+            }
+            last_moved = true;
+        } else {
+            positions.push(to_f64(&point.position()));
+            last_moved = false;
+        }
+    }
+    if last_moved
+        && positions.len() > 1
+        && moved.contains(&face.root_edge().vertex().point().handle)
+    {
+        positions.pop();
+    }
+    positions
+}
+
+/// Twice the area vector of a polygon (Newell's method).
+fn area_vector(positions: &[na::Vector3<f64>]) -> na::Vector3<f64> {
+    let mut normal = na::Vector3::zeros();
+    for (index, current) in positions.iter().enumerate() {
+        normal += current.cross(&positions[(index + 1) % positions.len()]);
+    }
+    normal
+}
+
+/// Number of corners which are not convex with respect to `normal`,
+/// counting nearly straight corners as well.
+fn reflex_corners(positions: &[na::Vector3<f64>], normal: &na::Vector3<f64>) -> usize {
+    let count = positions.len();
+    (0..count)
+        .filter(|index| {
+            let current = positions[*index];
+            let e0 = positions[(index + 1) % count] - current;
+            let e1 = positions[(index + count - 1) % count] - current;
+            let length = e0.norm() * e1.norm();
+            length <= f64::EPSILON || e0.cross(&e1).dot(normal) <= 1e-3 * length
+        })
+        .count()
+}
+
+/// Sums up the planes of the faces around each point, together with
+/// planes perpendicular to the boundary edges.
+fn initial_quadrics(mesh: &Mesh) -> HashMap<PointHandle, Quadric> {
+    let mut quadrics: HashMap<PointHandle, Quadric> = HashMap::new();
+    for face in mesh.faces() {
+        let area_vector = area_vector(&corner_positions(&face, &[], &Position::origin()));
+        let double_area = area_vector.norm();
+        if double_area <= f64::EPSILON {
+            continue;
+        }
+        let normal = area_vector / double_area;
+        let corner = to_f64(&face.root_edge().vertex().point().position());
+        let quadric = plane_quadric(&normal, &corner, double_area * 0.5);
+        for vert in face.vertices() {
+            *quadrics
+                .entry(vert.point().handle)
+                .or_insert_with(Quadric::zeros) += quadric;
+        }
+
+        for edge in face.edges().filter(|edge| edge.is_boundary()) {
+            let start = to_f64(&edge.vertex().point().position());
+            let end = to_f64(&edge.adjacent().vertex().point().position());
+            let direction = end - start;
+            if let Some(perpendicular) = direction.cross(&normal).try_normalize(f64::EPSILON) {
+                let quadric = plane_quadric(
+                    &perpendicular,
+                    &start,
+                    BOUNDARY_WEIGHT * direction.norm_squared(),
+                );
+                for point in [edge.vertex().point(), edge.adjacent().vertex().point()].iter() {
+                    *quadrics.entry(point.handle).or_insert_with(Quadric::zeros) += quadric;
+                }
+            }
+        }
+    }
+    quadrics
+}
+
+/// Points where several fans of faces meet, which can't be collapsed safely.
+fn non_manifold_points(mesh: &Mesh) -> HashSet<PointHandle> {
+    let mut outgoing: HashMap<PointHandle, (HalfEdgeHandle, usize)> = HashMap::new();
+    for edge in mesh.edges() {
+        outgoing
+            .entry(edge.vertex().point().handle)
+            .or_insert((edge.handle, 0))
+            .1 += 1;
+    }
+    outgoing
+        .into_iter()
+        .filter(|(_, (edge, count))| mesh.edge(*edge).vertex().edges().count() != *count)
+        .map(|(point, _)| point)
+        .collect()
+}
+
+/// A possible collapse, ordered so the cheapest one is popped first.
+struct Candidate {
+    cost: f64,
+    edge: HalfEdgeHandle,
+    points: (PointHandle, PointHandle),
+    stamps: (u32, u32),
+    position: Position,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cost == other.cost
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .cost
+            .partial_cmp(&self.cost)
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
+struct Decimator<'a> {
+    mesh: &'a mut Mesh,
+    quadrics: HashMap<PointHandle, Quadric>,
+    stamps: HashMap<PointHandle, u32>,
+    locked: HashSet<PointHandle>,
+    queue: BinaryHeap<Candidate>,
+}
+
+impl<'a> Decimator<'a> {
+    fn new(mesh: &'a mut Mesh) -> Self {
+        let quadrics = initial_quadrics(mesh);
+        let locked = non_manifold_points(mesh);
+        let mut decimator = Decimator {
+            mesh,
+            quadrics,
+            stamps: HashMap::new(),
+            locked,
+            queue: BinaryHeap::new(),
+        };
+        let edges: Vec<_> = decimator
+            .mesh
+            .full_edges()
+            .map(|edge| edge.half_edge().handle)
+            .collect();
+        for edge in edges {
+            decimator.push(edge);
+        }
+        decimator
+    }
+
+    fn quadric(&self, point: PointHandle) -> Quadric {
+        self.quadrics
+            .get(&point)
+            .cloned()
+            .unwrap_or_else(Quadric::zeros)
+    }
+
+    fn stamp(&self, point: PointHandle) -> u32 {
+        self.stamps.get(&point).cloned().unwrap_or(0)
+    }
+
+    fn endpoints(&self, edge: HalfEdgeHandle) -> (PointHandle, PointHandle) {
+        let edge = self.mesh.edge(edge);
+        (
+            edge.vertex().point().handle,
+            edge.adjacent().vertex().point().handle,
+        )
+    }
+
+    /// Queues the collapse of an edge at the position minimizing the error.
+    fn push(&mut self, edge: HalfEdgeHandle) {
+        let (a, b) = self.endpoints(edge);
+        if self.locked.contains(&a) || self.locked.contains(&b) {
+            return;
+        }
+        let quadric = self.quadric(a) + self.quadric(b);
+        let start = to_f64(&self.mesh.point(a).position());
+        let end = to_f64(&self.mesh.point(b).position());
+
+        let mut candidates = vec![start, end, (start + end) * 0.5];
+        let system = quadric.fixed_slice::<na::U3, na::U3>(0, 0).into_owned();
+        let scale = system.norm();
+        if system.determinant().abs() > 1e-9 * scale * scale * scale {
+            if let Some(inverse) = system.try_inverse() {
+                let rhs = -quadric.fixed_slice::<na::U3, na::U1>(0, 3).into_owned();
+                candidates.push(inverse * rhs);
+            }
+        }
+        let (cost, position) = candidates
+            .into_iter()
+            .map(|position| (quadric_error(&quadric, &position), position))
+            .min_by(|x, y| x.0.partial_cmp(&y.0).unwrap_or(Ordering::Equal))
+            .unwrap_or((0.0, start));
+
+        self.queue.push(Candidate {
+            cost,
+            edge,
+            points: (a, b),
+            stamps: (self.stamp(a), self.stamp(b)),
+            position: Position::new(position.x as f32, position.y as f32, position.z as f32),
+        });
+    }
+
+    fn is_current(&self, candidate: &Candidate) -> bool {
+        let (a, b) = candidate.points;
+        self.mesh.edge(candidate.edge).is_valid()
+            && self.endpoints(candidate.edge) == candidate.points
+            && (self.stamp(a), self.stamp(b)) == candidate.stamps
+    }
+
+    /// Checks whether any face around the edge would flip, degenerate or
+    /// gain a reflex corner.
+    fn flips_faces(&self, candidate: &Candidate) -> bool {
+        let edge = self.mesh.edge(candidate.edge);
+        let (a, b) = candidate.points;
+        // Traversing the faces retags their edges, so the circulators
+        // have to be done before looking at any of the faces.
+        let mut faces: Vec<FaceProxy> = edge.vertex().faces().collect();
+        faces.extend(edge.adjacent().vertex().faces());
+        faces.sort_by_key(|face| face.handle.index());
+        faces.dedup_by_key(|face| face.handle.index());
+        faces.iter().any(|face| {
+            let before = corner_positions(face, &[], &candidate.position);
+            let after = corner_positions(face, &[a, b], &candidate.position);
+            if after.len() < 3 {
+                return false;
+            }
+            let normal = area_vector(&before);
+            let area = area_vector(&after).dot(&normal);
+            area <= 1e-3 * normal.norm_squared()
+                || reflex_corners(&after, &normal) > reflex_corners(&before, &normal)
+        })
+    }
+
+    fn collapse(&mut self, candidate: &Candidate) -> bool {
+        if !self.mesh.is_collapse_ok(candidate.edge) || self.flips_faces(candidate) {
+            return false;
+        }
+        let (a, b) = candidate.points;
+        let vert = match self.mesh.collapse_edge(candidate.edge) {
+            Some(vert) => vert,
+            None => return false,
+        };
+        if let Some(point) = self.mesh.get(a) {
+            point.data_mut().position = candidate.position;
+        }
+
+        let quadric = self.quadric(a) + self.quadric(b);
+        self.quadrics.insert(a, quadric);
+        self.quadrics.remove(&b);
+        self.stamps.remove(&b);
+        *self.stamps.entry(a).or_insert(0) += 1;
+
+        let edges: Vec<_> = self.mesh.vertex(vert).edges().map(|e| e.handle).collect();
+        for edge in edges {
+            self.push(edge);
+        }
+        true
+    }
+}
+
+/// Reduces the number of faces by collapsing edges in order of their
+/// quadric error, until at most `target_face_count` faces are left.
+///
+/// The error of a collapse is the sum of squared distances of its new
+/// position to the planes of the faces merged into it, weighted by their
+/// area. Boundary edges are kept in place by additional planes, so open
+/// borders only get simplified along their own direction. When `max_error`
+/// is given, decimation stops before the first collapse exceeding it.
+///
+/// Every single collapse keeps the mesh manifold, and points where
+/// several fans of faces meet are never touched. Returns the number of
+/// faces left.
+pub fn decimate(mesh: &mut Mesh, target_face_count: usize, max_error: Option<f32>) -> usize {
+    let mut decimator = Decimator::new(mesh);
+    while decimator.mesh.face_count() > target_face_count {
+        let candidate = match decimator.queue.pop() {
+            Some(candidate) => candidate,
+            None => break,
+        };
+        if !decimator.is_current(&candidate) {
+            continue;
+        }
+        if max_error.map_or(false, |max_error| candidate.cost > f64::from(max_error)) {
+            break;
+        }
+        if !decimator.collapse(&candidate) {
+            log::trace!("Skipping collapse of edge {}.", candidate.edge.index());
+        }
+    }
+    decimator.mesh.face_count()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::*;
+    use crate::*;
+
+    #[test]
+    fn can_decimate_sphere() {
+        let _ = env_logger::try_init();
+        let mut mesh = Mesh::icosphere(1.0, 3);
+        assert_eq!(mesh.face_count(), 1280);
+
+        let face_count = decimate(&mut mesh, 128, None);
+        assert!(mesh.is_valid());
+        assert_eq!(face_count, mesh.face_count());
+        assert!((120..=128).contains(&face_count));
+        assert_eq!(euler_characteristic(&mesh), 2);
+        assert_eq!(mesh.full_edges().filter(|e| e.is_boundary()).count(), 0);
+        for vert in mesh.vertices() {
+            let radius = vert.point().position().coords.norm();
+            assert!((radius - 1.0).abs() < 0.1);
+        }
+    }
+
+    #[test]
+    fn decimation_keeps_boundary() {
+        let _ = env_logger::try_init();
+        let mut mesh = Mesh::grid(2.0, 2.0, 8, 8);
+
+        let face_count = decimate(&mut mesh, 4, None);
+        assert!(mesh.is_valid());
+        assert!(face_count < 16);
+        for vert in mesh.vertices() {
+            let position = vert.point().position();
+            assert!(position.y.abs() < 1e-5);
+            assert!(position.x.abs() <= 1.0 + 1e-5 && position.z.abs() <= 1.0 + 1e-5);
+        }
+        for edge in mesh.full_edges().filter(|e| e.is_boundary()) {
+            let (p0, p1) = edge.points();
+            for position in [p0.position(), p1.position()].iter() {
+                let distance = (1.0 - position.x.abs()).min(1.0 - position.z.abs());
+                assert!(distance < 1e-4);
+            }
+        }
+        let corners = mesh
+            .vertices()
+            .map(|vert| vert.point().position())
+            .filter(|p| (p.x.abs() - 1.0).abs() < 1e-5 && (p.z.abs() - 1.0).abs() < 1e-5)
+            .map(|p| (p.x > 0.0, p.z > 0.0))
+            .collect::<std::collections::HashSet<_>>();
+        assert_eq!(corners.len(), 4);
+    }
+
+    #[test]
+    fn decimation_respects_max_error() {
+        let _ = env_logger::try_init();
+        let mut cube = Mesh::unit_cube();
+        assert_eq!(decimate(&mut cube, 0, Some(1e-4)), 6);
+        assert!(cube.is_valid());
+
+        let mut mesh = Mesh::icosphere(1.0, 2);
+        let face_count = decimate(&mut mesh, 0, Some(1e-3));
+        assert!(mesh.is_valid());
+        assert!(face_count > 0 && face_count < 320);
+        assert_eq!(euler_characteristic(&mesh), 2);
+    }
+}
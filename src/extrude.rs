@@ -0,0 +1,403 @@
+//! Extrusion of faces and regions of faces.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::data::{ExtrudeMode, Normal, Position, VertexAttributes};
+use crate::elements::Point;
+use crate::handles::{FaceHandle, HalfEdgeHandle, PointHandle};
+use crate::mesh::Mesh;
+use crate::proxy::*;
+use crate::traits::*;
+use nalgebra as na;
+
+/// How far the extruded points are moved.
+#[derive(Debug, Copy, Clone)]
+enum Offset {
+    /// Along the averaged normal of the extruded faces around each point.
+    Distance(f32),
+    /// By the same vector for every point.
+    Vector(Normal),
+}
+
+impl Mesh {
+    fn attributes_of(&self, edge: HalfEdgeHandle) -> VertexAttributes {
+        self.edge(edge)
+            .vertex()
+            .data()
+            .map(|data| data.attrs.clone())
+            .unwrap_or_default()
+    }
+
+    /// Makes each pair of half-edges twins, the second one taking over the
+    /// crease of the first.
+    fn set_twins(&self, pairs: &[(HalfEdgeHandle, HalfEdgeHandle)]) {
+        for (a, b) in pairs.iter() {
+            let crease = self.edge(*a).data().map(|data| data.crease);
+            if let (Some(a_element), Some(b_element)) = (self.get(*a), self.get(*b)) {
+                a_element.data_mut().adjacent = *b;
+                b_element.data_mut().adjacent = *a;
+                if let Some(crease) = crease {
+                    b_element.data_mut().crease = crease;
+                }
+            }
+        }
+    }
+
+    /// Extrudes a connected set of faces, moving their points and adding a
+    /// quad for every edge on the outline of the region.
+    fn extrude_region(&mut self, faces: &[FaceHandle], offset: Offset) -> Vec<FaceHandle> {
+        let mut region = HashSet::new();
+        let faces: Vec<_> = faces
+            .iter()
+            .filter(|face| self.face(**face).is_valid() && region.insert(face.index()))
+            .cloned()
+            .collect();
+        let in_region = |face: FaceProxy| face.is_valid() && region.contains(&face.handle.index());
+
+        let mut corners: HashMap<PointHandle, Vec<HalfEdgeHandle>> = HashMap::new();
+        let mut normals: HashMap<PointHandle, Normal> = HashMap::new();
+        let mut outline = Vec::new();
+        for face in faces {
+            let face = self.face(face);
+            let normal = face.area_vector();
+            let edges: Vec<_> = face.edges().collect();
+            for edge in edges {
+                let point = edge.vertex().point().handle;
+                corners.entry(point).or_default().push(edge.handle);
+                *normals.entry(point).or_insert_with(na::zero) += normal;
+                if !in_region(edge.adjacent().face()) {
+                    outline.push((edge.handle, point));
+                }
+            }
+        }
+        let offset_of = |point: &PointHandle| match offset {
+            Offset::Distance(distance) => {
+                normals[point]
+                    .try_normalize(f32::EPSILON)
+                    .unwrap_or_else(na::zero)
+                    * distance
+            }
+            Offset::Vector(vector) => vector,
+        };
+
+        // Points on the outline stay in place for the neighboring faces and
+        // the region gets copies of them, all other points are just moved.
+        let outline_points: HashSet<_> = outline.iter().map(|(_, point)| *point).collect();
+        let mut copies: HashMap<PointHandle, PointHandle> = HashMap::new();
+        for (point, edges) in corners.iter() {
+            let position: Position = self.point(*point).position() + offset_of(point);
+            if outline_points.contains(point) {
+                let copy = self.add(Point::new(position));
+                for edge in edges {
+                    if let Some(vert) = self.edge(*edge).vertex().element() {
+                        vert.data_mut().point = copy;
+                    }
+                }
+                copies.insert(*point, copy);
+            } else if let Some(element) = self.get(*point) {
+                element.data_mut().position = position;
+            }
+        }
+
+        // Every outline edge gets a pair of edges leading up from its start,
+        // the downward one closes its own side face and the upward one the
+        // side face of the preceding outline edge.
+        let mut downs = HashMap::new();
+        let mut ups = HashMap::new();
+        for (edge, point) in outline.iter() {
+            let previous = {
+                let mut previous = self.edge(*edge).prev();
+                while in_region(previous.adjacent().face()) {
+                    previous = previous.adjacent().prev();
+                }
+                previous.handle
+            };
+            let attrs = self.attributes_of(*edge);
+            let (up, down) = self.make_edge((*point, copies[point]));
+            for vert in [up, down].iter() {
+                if let Some(vert) = self.edge(*vert).vertex().element() {
+                    vert.data_mut().attrs = attrs.clone();
+                }
+            }
+            downs.insert(*edge, down);
+            ups.insert(previous, up);
+        }
+
+        let mut sides = Vec::with_capacity(outline.len());
+        for (edge, start) in outline {
+            let (twin, end) = {
+                let edge = self.edge(edge);
+                (edge.adjacent().handle, edge.next().vertex().point().handle)
+            };
+            let (bottom, top) = self.make_edge((start, end));
+            self.set_twins(&[(twin, bottom), (edge, top)]);
+            let (start_attrs, end_attrs) = (
+                self.attributes_of(edge),
+                self.attributes_of(self.edge(edge).next().handle),
+            );
+            for (side, attrs) in [(bottom, start_attrs), (top, end_attrs)].iter() {
+                if let Some(vert) = self.edge(*side).vertex().element() {
+                    vert.data_mut().attrs = attrs.clone();
+                }
+            }
+            sides.push(self.add_face([bottom, ups[&edge], top, downs[&edge]].as_ref()));
+        }
+        sides
+    }
+
+    /// Shrinks the extruded faces of a zero length extrusion inwards, so
+    /// the side faces form a ring of quads within the original outline.
+    fn inset_region(&mut self, faces: &[FaceHandle], width: f32) -> Vec<FaceHandle> {
+        let sides = self.extrude_region(faces, Offset::Vector(na::zero()));
+
+        // The top edge of every side face runs along the outline of the
+        // region, the inward direction is to its left within its face.
+        let mut inward: HashMap<PointHandle, Vec<Normal>> = HashMap::new();
+        for side in sides.iter() {
+            let edge = self.face(*side).root_edge().next().next().adjacent();
+            let (start, end) = (edge.vertex().point(), edge.adjacent().vertex().point());
+            let normal = edge.face().area_vector();
+            let direction = normal
+                .cross(&(end.position() - start.position()))
+                .try_normalize(f32::EPSILON)
+                .unwrap_or_else(na::zero);
+            for point in [start.handle, end.handle].iter() {
+                inward.entry(*point).or_default().push(direction);
+            }
+        }
+
+        for (point, directions) in inward {
+            let offset = match directions.as_slice() {
+                // Moves the point by `width` away from both edges.
+                [a, b] if 1.0 + a.dot(b) > f32::EPSILON => (a + b) * (width / (1.0 + a.dot(b))),
+                directions => {
+                    let sum: Normal = directions.iter().sum();
+                    sum * (width / directions.len() as f32)
+                }
+            };
+            if let Some(point) = self.get(point) {
+                point.data_mut().position += offset;
+            }
+        }
+        sides
+    }
+
+    /// Insets faces, shrinking them inwards by `width` and surrounding them
+    /// with a ring of quads.
+    ///
+    /// In `ExtrudeMode::Region` only the outline of the set of faces is
+    /// inset. Returns the faces of the ring.
+    pub fn inset_faces(
+        &mut self,
+        faces: &[FaceHandle],
+        width: f32,
+        mode: ExtrudeMode,
+    ) -> Vec<FaceHandle> {
+        match mode {
+            ExtrudeMode::Region => self.inset_region(faces, width),
+            ExtrudeMode::Individual => faces
+                .iter()
+                .flat_map(|face| self.inset_region(&[*face], width))
+                .collect(),
+        }
+    }
+
+    fn extrude_faces(
+        &mut self,
+        faces: &[FaceHandle],
+        offset: Offset,
+        mode: ExtrudeMode,
+    ) -> Vec<FaceHandle> {
+        match mode {
+            ExtrudeMode::Region => self.extrude_region(faces, offset),
+            ExtrudeMode::Individual => faces
+                .iter()
+                .flat_map(|face| self.extrude_region(&[*face], offset))
+                .collect(),
+        }
+    }
+}
+
+impl Extrude<(FaceHandle, f32)> for Mesh {
+    /// Extrudes a face along its normal by `distance`.
+    fn extrude(&mut self, (face, distance): (FaceHandle, f32)) -> Vec<FaceHandle> {
+        log::trace!("- Extrude<(FaceHandle({}), f32)>", face.index());
+        self.extrude_region(&[face], Offset::Distance(distance))
+    }
+}
+
+impl Extrude<(FaceHandle, Normal)> for Mesh {
+    /// Extrudes a face by moving it by `vector`.
+    fn extrude(&mut self, (face, vector): (FaceHandle, Normal)) -> Vec<FaceHandle> {
+        log::trace!("- Extrude<(FaceHandle({}), Normal)>", face.index());
+        self.extrude_region(&[face], Offset::Vector(vector))
+    }
+}
+
+impl Extrude<(&[FaceHandle], f32)> for Mesh {
+    /// Extrudes a region of faces, moving each point along the averaged
+    /// normal of the extruded faces around it by `distance`.
+    fn extrude(&mut self, (faces, distance): (&[FaceHandle], f32)) -> Vec<FaceHandle> {
+        log::trace!("- Extrude<(&[FaceHandle], f32)>");
+        self.extrude_faces(faces, Offset::Distance(distance), ExtrudeMode::Region)
+    }
+}
+
+impl Extrude<(&[FaceHandle], Normal)> for Mesh {
+    /// Extrudes a region of faces by moving it by `vector`.
+    fn extrude(&mut self, (faces, vector): (&[FaceHandle], Normal)) -> Vec<FaceHandle> {
+        log::trace!("- Extrude<(&[FaceHandle], Normal)>");
+        self.extrude_faces(faces, Offset::Vector(vector), ExtrudeMode::Region)
+    }
+}
+
+impl Extrude<(&[FaceHandle], f32, ExtrudeMode)> for Mesh {
+    fn extrude(
+        &mut self,
+        (faces, distance, mode): (&[FaceHandle], f32, ExtrudeMode),
+    ) -> Vec<FaceHandle> {
+        log::trace!("- Extrude<(&[FaceHandle], f32, {:?})>", mode);
+        self.extrude_faces(faces, Offset::Distance(distance), mode)
+    }
+}
+
+impl Extrude<(&[FaceHandle], Normal, ExtrudeMode)> for Mesh {
+    fn extrude(
+        &mut self,
+        (faces, vector, mode): (&[FaceHandle], Normal, ExtrudeMode),
+    ) -> Vec<FaceHandle> {
+        log::trace!("- Extrude<(&[FaceHandle], Normal, {:?})>", mode);
+        self.extrude_faces(faces, Offset::Vector(vector), mode)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::*;
+    use crate::*;
+
+    #[test]
+    fn can_extrude_face_of_cube() {
+        let _ = env_logger::try_init();
+        let mut mesh = Mesh::unit_cube();
+        let top = mesh
+            .faces()
+            .find(|face| face.vertices().all(|vert| vert.point().position().y == 1.0))
+            .unwrap()
+            .handle;
+
+        let sides = mesh.extrude((top, Normal::new(0.0, 1.0, 0.0)));
+        assert!(mesh.is_valid());
+        assert_eq!(sides.len(), 4);
+        assert_eq!(mesh.face_count(), 10);
+        assert_eq!(mesh.point_count(), 12);
+        assert_eq!(boundary_edge_count(&mesh), 0);
+        assert!(mesh
+            .face(top)
+            .vertices()
+            .all(|vert| vert.point().position().y == 2.0));
+        for side in sides {
+            assert_eq!(mesh.face(side).vertices().count(), 4);
+        }
+    }
+
+    #[test]
+    fn extrudes_along_normal() {
+        let _ = env_logger::try_init();
+        let mut mesh = Mesh::grid(1.0, 1.0, 1, 1);
+        let face = mesh.faces().next().unwrap().handle;
+        let sides = mesh.extrude((face, 0.5));
+        assert!(mesh.is_valid());
+        assert_eq!(sides.len(), 4);
+        assert_eq!(mesh.face_count(), 5);
+        assert_eq!(boundary_edge_count(&mesh), 4);
+        assert!(mesh
+            .face(face)
+            .vertices()
+            .all(|vert| (vert.point().position().y.abs() - 0.5).abs() < 1e-6));
+        for side in sides {
+            assert!(mesh.face(side).vertices().all(|vert| {
+                let position = vert.point().position();
+                position.x.abs() == 0.5 || position.z.abs() == 0.5
+            }));
+        }
+    }
+
+    #[test]
+    fn can_extrude_region() {
+        let _ = env_logger::try_init();
+        let mut mesh = Mesh::grid(1.0, 1.0, 2, 2);
+        let faces: Vec<_> = mesh.faces().map(|face| face.handle).collect();
+        let sides = mesh.extrude((faces.as_slice(), Normal::new(0.0, 1.0, 0.0)));
+        assert!(mesh.is_valid());
+        assert_eq!(sides.len(), 8);
+        assert_eq!(mesh.face_count(), 12);
+        // The center point is only moved, the outline is duplicated.
+        assert_eq!(mesh.point_count(), 17);
+        assert_eq!(boundary_edge_count(&mesh), 8);
+    }
+
+    #[test]
+    fn can_extrude_faces_individually() {
+        let _ = env_logger::try_init();
+        let mut mesh = Mesh::grid(1.0, 1.0, 2, 2);
+        let faces: Vec<_> = mesh.faces().map(|face| face.handle).collect();
+        let sides = mesh.extrude((faces.as_slice(), 0.25, ExtrudeMode::Individual));
+        assert!(mesh.is_valid());
+        assert_eq!(sides.len(), 16);
+        assert_eq!(mesh.face_count(), 20);
+        assert_eq!(mesh.point_count(), 9 + 16);
+        assert_eq!(boundary_edge_count(&mesh), 8);
+        for face in faces {
+            assert!(mesh
+                .face(face)
+                .vertices()
+                .all(|vert| (vert.point().position().y.abs() - 0.25).abs() < 1e-6));
+        }
+    }
+
+    #[test]
+    fn can_inset_faces() {
+        let _ = env_logger::try_init();
+        let mut mesh = Mesh::grid(2.0, 2.0, 2, 2);
+        let faces: Vec<_> = mesh.faces().map(|face| face.handle).collect();
+        let ring = mesh.inset_faces(&faces, 0.25, ExtrudeMode::Region);
+        assert!(mesh.is_valid());
+        assert_eq!(ring.len(), 8);
+        assert_eq!(mesh.face_count(), 12);
+        for vert in mesh.vertices() {
+            assert_eq!(vert.point().position().y, 0.0);
+        }
+        // The outline of the region moves inwards, the inner point stays.
+        for face in faces.iter() {
+            for vert in mesh.face(*face).vertices() {
+                let position = vert.point().position();
+                assert!(position.x.abs() < 0.76 && position.z.abs() < 0.76);
+            }
+        }
+        for face in ring {
+            assert_eq!(mesh.face(face).vertices().count(), 4);
+        }
+    }
+
+    #[test]
+    fn can_inset_faces_individually() {
+        let _ = env_logger::try_init();
+        let mut mesh = Mesh::grid(2.0, 2.0, 2, 2);
+        let faces: Vec<_> = mesh.faces().map(|face| face.handle).collect();
+        let ring = mesh.inset_faces(&faces, 0.25, ExtrudeMode::Individual);
+        assert!(mesh.is_valid());
+        assert_eq!(ring.len(), 16);
+        for face in faces.iter() {
+            let positions: Vec<_> = mesh
+                .face(*face)
+                .vertices()
+                .map(|vert| vert.point().position())
+                .collect();
+            for (index, position) in positions.iter().enumerate() {
+                let next = positions[(index + 1) % positions.len()];
+                assert!(((next - position).norm() - 0.5).abs() < 1e-5);
+            }
+        }
+    }
+}
@@ -13,7 +13,7 @@ pub const INVALID_COMPONENT_INDEX: Index = std::u32::MAX;
 pub const IGNORED_GENERATION: Generation = 0;
 
 /// Type-safe index into kernel storage.
-#[derive(Debug, Clone, Eq)]
+#[derive(Debug, Clone)]
 pub struct Handle<T> {
     index: Index,
     generation: Generation,
@@ -69,8 +69,14 @@ impl<T: Element> ElementHandle for Handle<T> {
 
 impl<T> PartialOrd for Handle<T> {
     fn partial_cmp(&self, other: &Handle<T>) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Handle<T> {
+    fn cmp(&self, other: &Handle<T>) -> cmp::Ordering {
         // Only the index should matter when it comes to ordering
-        self.index.partial_cmp(&other.index)
+        self.index.cmp(&other.index)
     }
 }
 
@@ -84,6 +90,8 @@ impl<T> PartialEq for Handle<T> {
     }
 }
 
+impl<T> Eq for Handle<T> {}
+
 impl<T> IsValid for Handle<T> {
     fn is_valid(&self) -> bool {
         self.index != INVALID_COMPONENT_INDEX
@@ -94,3 +102,37 @@ pub type HalfEdgeHandle = Handle<HalfEdge>;
 pub type FaceHandle = Handle<Face>;
 pub type VertexHandle = Handle<Vertex>;
 pub type PointHandle = Handle<Point>;
+
+/// Handle of an undirected edge.
+///
+/// A full edge is identified by the half-edge of its adjacent pair
+/// with the lower index.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FullEdgeHandle(HalfEdgeHandle);
+
+impl FullEdgeHandle {
+    /// Builds the canonical handle for an adjacent pair of half-edges.
+    pub fn new(e0: HalfEdgeHandle, e1: HalfEdgeHandle) -> Self {
+        if e1.is_valid() && e1.index() < e0.index() {
+            FullEdgeHandle(e1)
+        } else {
+            FullEdgeHandle(e0)
+        }
+    }
+
+    /// The half-edge used to identify this full edge.
+    pub fn half_edge(&self) -> HalfEdgeHandle {
+        self.0
+    }
+
+    /// Index of the half-edge identifying this full edge.
+    pub fn index(&self) -> Index {
+        self.0.index()
+    }
+}
+
+impl IsValid for FullEdgeHandle {
+    fn is_valid(&self) -> bool {
+        self.0.is_valid()
+    }
+}
@@ -0,0 +1,481 @@
+//! Walking and closing boundary loops.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::data::{HoleFilling, Normal, Position};
+use crate::handles::{FaceHandle, HalfEdgeHandle, PointHandle, VertexHandle};
+use crate::mesh::Mesh;
+use crate::proxy::*;
+use crate::traits::*;
+use nalgebra as na;
+
+/// Cost of a partial triangulation as the largest dihedral angle and the
+/// total area.
+type Weight = (f32, f32);
+
+const NO_TRIANGULATION: Weight = (f32::INFINITY, f32::INFINITY);
+
+fn triangle_normal(a: &Position, b: &Position, c: &Position) -> Normal {
+    (b - a).cross(&(c - a))
+}
+
+fn angle_between(n0: &Normal, n1: &Normal) -> f32 {
+    match (
+        n0.try_normalize(f32::EPSILON),
+        n1.try_normalize(f32::EPSILON),
+    ) {
+        (Some(n0), Some(n1)) => n0.dot(&n1).clamp(-1.0, 1.0).acos(),
+        _ => 0.0,
+    }
+}
+
+impl Mesh {
+    /// Collects the boundary loop containing the given half-edge, starting
+    /// with the half-edge without a face.
+    ///
+    /// Returns an empty list if neither side of the edge is on the boundary.
+    pub fn boundary_loop(&self, edge: HalfEdgeHandle) -> Vec<HalfEdgeHandle> {
+        let edge = self.edge(edge);
+        let start = if !edge.face().is_valid() {
+            edge
+        } else if !edge.adjacent().face().is_valid() {
+            edge.adjacent()
+        } else {
+            return Vec::new();
+        };
+
+        let mut boundary = vec![start.handle];
+        let mut current = start;
+        loop {
+            // Edges are tagged by the circulator, so it has to be done
+            // before looking at the next edge.
+            let next = current
+                .adjacent()
+                .vertex()
+                .edges()
+                .find(|e| !e.face().is_valid());
+            match next {
+                Some(next) if next.handle.index() == start.handle.index() => break,
+                Some(next) if boundary.len() <= self.edge_count() => {
+                    boundary.push(next.handle);
+                    current = next;
+                }
+                _ => {
+                    log::warn!(
+                        "Boundary loop at edge {} is not closed.",
+                        start.handle.index()
+                    );
+                    break;
+                }
+            }
+        }
+        boundary
+    }
+
+    /// Collects all boundary loops of the mesh.
+    pub fn boundary_loops(&self) -> Vec<Vec<HalfEdgeHandle>> {
+        let mut visited = HashSet::new();
+        let mut loops = Vec::new();
+        for edge in self.edges() {
+            if edge.face().is_valid() || visited.contains(&edge.handle.index()) {
+                continue;
+            }
+            let boundary = self.boundary_loop(edge.handle);
+            visited.extend(boundary.iter().map(|e| e.index()));
+            loops.push(boundary);
+        }
+        loops
+    }
+
+    /// Closes the boundary loop containing `edge` with new faces.
+    ///
+    /// With `fair` set, the triangles of the patch are refined to about the
+    /// length of the boundary edges and the new points are moved to form a
+    /// smooth membrane over the hole. A single polygon is never refined.
+    ///
+    /// Returns the faces covering the hole.
+    pub fn fill_hole(
+        &mut self,
+        edge: HalfEdgeHandle,
+        filling: HoleFilling,
+        fair: bool,
+    ) -> Vec<FaceHandle> {
+        let boundary = self.boundary_loop(edge);
+        if boundary.len() < 3 {
+            log::error!(
+                "Unable to fill hole: edge {} is not on a boundary loop with at least 3 edges.",
+                edge.index()
+            );
+            return Vec::new();
+        }
+        let faces = match filling {
+            HoleFilling::Polygon => vec![self.add_face(boundary.as_slice())],
+            HoleFilling::MinimumArea => self.triangulate_hole(&boundary, false),
+            HoleFilling::MinimumDihedral => self.triangulate_hole(&boundary, true),
+        };
+        if fair && filling != HoleFilling::Polygon && !faces.is_empty() {
+            let length = boundary
+                .iter()
+                .map(|e| {
+                    let (p0, p1) = self.edge(*e).full_edge().points();
+                    (p1.position() - p0.position()).norm()
+                })
+                .sum::<f32>()
+                / boundary.len() as f32;
+            self.fair_patch(faces, length)
+        } else {
+            faces
+        }
+    }
+
+    /// Triangulates the hole by dynamic programming over the loop, see
+    /// Liepa, "Filling Holes in Meshes".
+    fn triangulate_hole(&mut self, boundary: &[HalfEdgeHandle], dihedral: bool) -> Vec<FaceHandle> {
+        let count = boundary.len();
+        let points: Vec<PointHandle> = boundary
+            .iter()
+            .map(|e| self.edge(*e).vertex().point().handle)
+            .collect();
+        let positions: Vec<Position> = points.iter().map(|p| self.point(*p).position()).collect();
+        // Normals of the faces on the other side of each boundary edge.
+        let outside: Vec<Normal> = boundary
+            .iter()
+            .map(|e| {
+                let face = self.edge(*e).adjacent().face();
+                let corners: Vec<_> = face.vertices().map(|v| v.point().position()).collect();
+                let mut normal: Normal = na::zero();
+                for (index, current) in corners.iter().enumerate() {
+                    normal += current
+                        .coords
+                        .cross(&corners[(index + 1) % corners.len()].coords);
+                }
+                normal
+            })
+            .collect();
+        let neighbors: Vec<HashSet<PointHandle>> = boundary
+            .iter()
+            .map(|e| {
+                self.edge(*e)
+                    .vertex()
+                    .edges()
+                    .map(|e| e.adjacent().vertex().point().handle)
+                    .collect()
+            })
+            .collect();
+
+        let mut weights = vec![vec![NO_TRIANGULATION; count]; count];
+        let mut splits = vec![vec![0; count]; count];
+        for i in 0..count - 1 {
+            weights[i][i + 1] = (0.0, 0.0);
+        }
+        let normal_across = |splits: &Vec<Vec<usize>>, i: usize, k: usize| -> Normal {
+            if k == i + 1 {
+                outside[i]
+            } else {
+                let m = splits[i][k];
+                triangle_normal(&positions[i], &positions[m], &positions[k])
+            }
+        };
+        for span in 2..count {
+            for i in 0..count - span {
+                let k = i + span;
+                let closing = i == 0 && k == count - 1;
+                // A new edge between points that are already connected
+                // would make the mesh non-manifold.
+                if !closing && (points[i] == points[k] || neighbors[i].contains(&points[k])) {
+                    continue;
+                }
+                for m in i + 1..k {
+                    let (left, right) = (weights[i][m], weights[m][k]);
+                    if left.0.is_infinite() || right.0.is_infinite() {
+                        continue;
+                    }
+                    let normal = triangle_normal(&positions[i], &positions[m], &positions[k]);
+                    let area = 0.5 * normal.norm();
+                    let mut angle = left.0.max(right.0);
+                    if dihedral {
+                        angle = angle
+                            .max(angle_between(&normal, &normal_across(&splits, i, m)))
+                            .max(angle_between(&normal, &normal_across(&splits, m, k)));
+                        if closing {
+                            angle = angle.max(angle_between(&normal, &outside[count - 1]));
+                        }
+                    }
+                    let weight = (angle, left.1 + right.1 + area);
+                    let current = weights[i][k];
+                    let better = if dihedral && (weight.0 - current.0).abs() > 1e-5 {
+                        weight.0 < current.0
+                    } else {
+                        weight.1 < current.1
+                    };
+                    if better {
+                        weights[i][k] = weight;
+                        splits[i][k] = m;
+                    }
+                }
+            }
+        }
+        if weights[0][count - 1].1.is_infinite() {
+            log::error!("Unable to triangulate hole without creating duplicate edges.");
+            return Vec::new();
+        }
+
+        let mut triangles = Vec::with_capacity(count - 2);
+        let mut pending = vec![(0, count - 1)];
+        while let Some((i, k)) = pending.pop() {
+            if k < i + 2 {
+                continue;
+            }
+            let m = splits[i][k];
+            triangles.push((i, m, k));
+            pending.push((i, m));
+            pending.push((m, k));
+        }
+
+        let mut half_edges: HashMap<(usize, usize), HalfEdgeHandle> = HashMap::new();
+        for (index, edge) in boundary.iter().enumerate() {
+            half_edges.insert((index, (index + 1) % count), *edge);
+        }
+        let corner_vertices: Vec<VertexHandle> = boundary
+            .iter()
+            .map(|e| self.edge(*e).vertex().handle)
+            .collect();
+        let mut faces = Vec::with_capacity(triangles.len());
+        for (i, m, k) in triangles {
+            let mut loop_edges = Vec::with_capacity(3);
+            for (from, to) in [(i, m), (m, k), (k, i)].iter() {
+                let edge = match half_edges.get(&(*from, *to)) {
+                    Some(edge) => *edge,
+                    None => {
+                        let (e0, e1) = self.make_edge((points[*from], points[*to]));
+                        for (edge, corner) in [(e0, *from), (e1, *to)].iter() {
+                            let attrs = self
+                                .vertex(corner_vertices[*corner])
+                                .data()
+                                .map(|data| data.attrs.clone())
+                                .unwrap_or_default();
+                            if let Some(vertex) = self.get(self.edge(*edge).vertex().handle) {
+                                vertex.data_mut().attrs = attrs;
+                            }
+                        }
+                        half_edges.insert((*to, *from), e1);
+                        e0
+                    }
+                };
+                loop_edges.push(edge);
+            }
+            faces.push(self.add_face(loop_edges.as_slice()));
+        }
+        faces
+    }
+
+    /// Refines the triangles of a patch and relaxes the new points.
+    fn fair_patch(&mut self, faces: Vec<FaceHandle>, length: f32) -> Vec<FaceHandle> {
+        let mut patch: HashSet<u32> = faces.iter().map(|f| f.index()).collect();
+        // Points are kept rather than vertices, since flips move vertices
+        // onto other points.
+        let mut inserted: HashSet<u32> = HashSet::new();
+        let inside = |patch: &HashSet<u32>, edge: &FullEdgeProxy| {
+            let (f0, f1) = edge.faces();
+            patch.contains(&f0.handle.index()) && patch.contains(&f1.handle.index())
+        };
+
+        for _ in 0..10 {
+            let long_edges: Vec<HalfEdgeHandle> = self
+                .full_edges()
+                .filter(|e| inside(&patch, e))
+                .filter(|e| {
+                    let (p0, p1) = e.points();
+                    (p1.position() - p0.position()).norm() > length * 4.0 / 3.0
+                })
+                .map(|e| e.half_edge().handle)
+                .collect();
+            if long_edges.is_empty() {
+                break;
+            }
+            for edge in long_edges {
+                let (p0, p1) = self.edge(edge).full_edge().points();
+                let midpoint = Position::from((p0.position().coords + p1.position().coords) * 0.5);
+                let vert = self.split_edge(edge, midpoint);
+                let new_faces: Vec<_> = self
+                    .vertex(vert)
+                    .faces()
+                    .map(|f| f.handle.index())
+                    .collect();
+                patch.extend(new_faces);
+                inserted.insert(self.vertex(vert).point().handle.index());
+            }
+
+            // Delaunay flips keep the refined triangles from degenerating.
+            let inner_edges: Vec<HalfEdgeHandle> = self
+                .full_edges()
+                .filter(|e| inside(&patch, e))
+                .map(|e| e.half_edge().handle)
+                .collect();
+            for edge in inner_edges {
+                let opposite_angles = {
+                    let edge = self.edge(edge);
+                    let (a, b) = (
+                        edge.vertex().point().position(),
+                        edge.adjacent().vertex().point().position(),
+                    );
+                    let c = edge.prev().vertex().point().position();
+                    let d = edge.adjacent().prev().vertex().point().position();
+                    (a - c).angle(&(b - c)) + (a - d).angle(&(b - d))
+                };
+                if opposite_angles > std::f32::consts::PI && self.is_flip_ok(edge) {
+                    let _ = self.flip_edge(edge);
+                }
+            }
+        }
+
+        // Only points surrounded by the patch are relaxed, so the rim and
+        // the rest of the mesh stay in place.
+        let mut relaxed: HashMap<u32, VertexHandle> = HashMap::new();
+        for vert in self.vertices() {
+            let point = vert.point().handle.index();
+            if inserted.contains(&point) && !relaxed.contains_key(&point) {
+                relaxed.insert(point, vert.handle);
+            }
+        }
+        let relaxed: Vec<VertexHandle> = relaxed
+            .values()
+            .cloned()
+            .filter(|vert| {
+                self.vertex(*vert)
+                    .edges()
+                    .all(|e| patch.contains(&e.face().handle.index()))
+            })
+            .collect();
+        for _ in 0..50 {
+            let moved: Vec<_> = relaxed
+                .iter()
+                .map(|vert| {
+                    let vert = self.vertex(*vert);
+                    let neighbors: Vec<_> = vert
+                        .edges()
+                        .map(|e| e.adjacent().vertex().point().position().coords)
+                        .collect();
+                    let sum = neighbors
+                        .iter()
+                        .fold(na::Vector3::zeros(), |sum, p| sum + p);
+                    (
+                        vert.point().handle,
+                        Position::from(sum / neighbors.len() as f32),
+                    )
+                })
+                .collect();
+            for (point, position) in moved {
+                if let Some(point) = self.get(point) {
+                    point.data_mut().position = position;
+                }
+            }
+        }
+
+        self.faces()
+            .filter(|face| patch.contains(&face.handle.index()))
+            .map(|face| face.handle)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn can_walk_boundary_loops() {
+        let _ = env_logger::try_init();
+        let mesh = Mesh::cylinder(1.0, 1.0, 8, false);
+        let loops = mesh.boundary_loops();
+        assert_eq!(loops.len(), 2);
+        for boundary in loops.iter() {
+            assert_eq!(boundary.len(), 8);
+            for (index, edge) in boundary.iter().enumerate() {
+                let edge = mesh.edge(*edge);
+                let next = mesh.edge(boundary[(index + 1) % boundary.len()]);
+                assert!(!edge.face().is_valid());
+                assert_eq!(
+                    edge.adjacent().vertex().point().handle,
+                    next.vertex().point().handle
+                );
+            }
+        }
+
+        let face_edge = mesh.edges().find(|e| !e.is_boundary()).unwrap().handle;
+        assert!(mesh.boundary_loop(face_edge).is_empty());
+        assert!(Mesh::unit_cube().boundary_loops().is_empty());
+    }
+
+    #[test]
+    fn can_fill_hole_with_polygon() {
+        let _ = env_logger::try_init();
+        let mut mesh = Mesh::cylinder(1.0, 1.0, 8, false);
+        let edge = mesh.boundary_loops()[0][0];
+        let faces = mesh.fill_hole(edge, HoleFilling::Polygon, false);
+        assert_eq!(faces.len(), 1);
+        assert!(mesh.is_valid());
+        assert_eq!(mesh.face(faces[0]).vertices().count(), 8);
+        assert_eq!(mesh.boundary_loops().len(), 1);
+    }
+
+    #[test]
+    fn can_triangulate_holes() {
+        let _ = env_logger::try_init();
+        for filling in [HoleFilling::MinimumArea, HoleFilling::MinimumDihedral].iter() {
+            let mut mesh = Mesh::cylinder(1.0, 1.0, 8, false);
+            for boundary in mesh.boundary_loops() {
+                let faces = mesh.fill_hole(boundary[0], *filling, false);
+                assert_eq!(faces.len(), 6);
+                for face in faces {
+                    let corners: Vec<_> = mesh
+                        .face(face)
+                        .vertices()
+                        .map(|v| v.point().position())
+                        .collect();
+                    assert_eq!(corners.len(), 3);
+                    // The caps have to face away from the center.
+                    let normal = (corners[1] - corners[0]).cross(&(corners[2] - corners[0]));
+                    assert!(normal.y * corners[0].y > 0.0);
+                }
+            }
+            assert!(mesh.is_valid());
+            assert!(mesh.boundary_loops().is_empty());
+            assert_eq!(mesh.face_count(), 8 + 12);
+        }
+    }
+
+    #[test]
+    fn faired_patch_stays_in_plane() {
+        let _ = env_logger::try_init();
+        let mut mesh = Mesh::cylinder(1.0, 1.0, 16, false);
+        let edge = mesh.boundary_loops()[0][0];
+        let height = mesh.edge(edge).vertex().point().position().y;
+        let faces = mesh.fill_hole(edge, HoleFilling::MinimumDihedral, true);
+        assert!(faces.len() > 14);
+        assert!(mesh.is_valid());
+        assert_eq!(mesh.boundary_loops().len(), 1);
+        for face in faces {
+            for vert in mesh.face(face).vertices() {
+                let position = vert.point().position();
+                assert!((position.y - height).abs() < 1e-5);
+                assert!(position.coords.xz().norm() <= 1.0 + 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn fairing_keeps_the_surrounding_mesh() {
+        let _ = env_logger::try_init();
+        for segments in [16, 24, 32].iter() {
+            let mut mesh = Mesh::cylinder(2.0, 1.0, *segments, false);
+            let before: Vec<_> = mesh.points().map(|p| (p.handle, p.position())).collect();
+            let edge = mesh.boundary_loops()[0][0];
+            let _ = mesh.fill_hole(edge, HoleFilling::MinimumDihedral, true);
+            assert!(mesh.is_valid());
+            for (point, position) in before {
+                assert_eq!(mesh.point(point).position(), position);
+            }
+        }
+    }
+}
@@ -2,24 +2,43 @@
 
 use super::*;
 
+/// Visits the outgoing half-edges of all vertices sharing a point.
+///
+/// The circulator first rotates in one direction until it either gets back
+/// to the starting edge or runs into the boundary, in which case it resumes
+/// from the starting edge in the opposite direction. Boundary half-edges are
+/// included, so for a point on the boundary the first and last items are
+/// the edges without a face or with a faceless twin.
+///
+/// Visited edges are tagged, so traversing the faces around the point has to
+/// wait until the circulator is done.
 #[derive(Debug)]
 pub struct VertexCirculator<'mesh> {
     tag: Tag,
     vert: VertexProxy<'mesh>,
     last_edge: Option<HalfEdgeProxy<'mesh>>,
-    central_point: PointHandle,
+    reversed: bool,
 }
 
 impl<'mesh> VertexCirculator<'mesh> {
     pub fn new(tag: Tag, vert: VertexProxy<'mesh>) -> Self {
         assert!(vert.is_valid());
-        let central_point = vert.point().handle;
-        dbg!(vert.handle.index(), central_point.index());
         VertexCirculator {
             tag,
             vert,
             last_edge: None,
-            central_point,
+            reversed: false,
+        }
+    }
+
+    fn visit(&mut self, edge: HalfEdgeProxy<'mesh>) -> Option<HalfEdgeProxy<'mesh>> {
+        let element = edge.element()?;
+        if element.tag() == self.tag {
+            None
+        } else {
+            element.set_tag(self.tag);
+            self.last_edge = Some(edge);
+            self.last_edge
         }
     }
 }
@@ -28,19 +47,29 @@ impl<'mesh> Iterator for VertexCirculator<'mesh> {
     type Item = HalfEdgeProxy<'mesh>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if let Some(last_edge) = self.last_edge {
-            self.last_edge = Some(last_edge.prev().adjacent());
-        } else {
-            self.last_edge = Some(self.vert.edge());
-        }
-        if let Some(element) = self.last_edge.and_then(|e| e.element()) {
-            if element.tag() == self.tag {
-                return None;
-            } else {
-                element.set_tag(self.tag);
+        let last_edge = match self.last_edge {
+            Some(last_edge) => last_edge,
+            None => return self.visit(self.vert.edge()),
+        };
+        if !self.reversed {
+            if last_edge.face().is_valid() {
+                let next_edge = last_edge.prev().adjacent();
+                if next_edge.is_valid() {
+                    return self.visit(next_edge);
+                }
             }
+            self.reversed = true;
+            let first_edge = self.vert.edge();
+            if first_edge.adjacent().face().is_valid() {
+                return self.visit(first_edge.adjacent().next());
+            }
+            return None;
+        }
+        if last_edge.adjacent().face().is_valid() {
+            self.visit(last_edge.adjacent().next())
+        } else {
+            None
         }
-        self.last_edge
     }
 }
 
@@ -49,6 +78,25 @@ pub struct VertexFaceCirculator<'mesh> {
     inner_iter: VertexCirculator<'mesh>,
 }
 
+impl<'mesh> VertexFaceCirculator<'mesh> {
+    pub fn new(vert: VertexProxy<'mesh>) -> Self {
+        VertexFaceCirculator {
+            inner_iter: vert.edges(),
+        }
+    }
+}
+
+impl<'mesh> Iterator for VertexFaceCirculator<'mesh> {
+    type Item = FaceProxy<'mesh>;
+
+    /// Skips the boundary edges, which don't have a face.
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner_iter
+            .find(|edge| edge.face().is_valid())
+            .map(|edge| edge.face())
+    }
+}
+
 #[derive(Debug)]
 pub struct FaceEdges<'mesh> {
     tag: Tag,
@@ -119,7 +167,7 @@ impl<'mesh> Iterator for FaceVertices<'mesh> {
 
 #[derive(Debug)]
 pub struct FaceNeighbors<'mesh> {
-    inner_iter: FaceEdges<'mesh>
+    inner_iter: FaceEdges<'mesh>,
 }
 
 impl<'mesh> FaceNeighbors<'mesh> {
@@ -139,26 +187,36 @@ impl<'mesh> Iterator for FaceNeighbors<'mesh> {
     }
 }
 
+/// Fan triangulation of a face around the vertex of its root edge.
+#[derive(Debug)]
 pub struct FaceTriangles<'mesh> {
-    _vertices: Vec<VertexProxy<'mesh>>,
+    vertices: Vec<VertexProxy<'mesh>>,
+    offset: usize,
 }
 
 impl<'mesh> Iterator for FaceTriangles<'mesh> {
-    type Item = (
-        &'mesh VertexProxy<'mesh>,
-        &'mesh VertexProxy<'mesh>,
-        &'mesh VertexProxy<'mesh>
-    );
+    type Item = (VertexProxy<'mesh>, VertexProxy<'mesh>, VertexProxy<'mesh>);
 
     fn next(&mut self) -> Option<Self::Item> {
-        None
+        if self.offset + 1 < self.vertices.len() {
+            let triangle = (
+                self.vertices[0],
+                self.vertices[self.offset],
+                self.vertices[self.offset + 1],
+            );
+            self.offset += 1;
+            Some(triangle)
+        } else {
+            None
+        }
     }
 }
 
 impl<'mesh> FaceTriangles<'mesh> {
     pub fn new(face: FaceProxy<'mesh>) -> Self {
         FaceTriangles {
-            _vertices: face.vertices().collect(),
+            vertices: face.vertices().collect(),
+            offset: 1,
         }
     }
 }
@@ -273,4 +331,153 @@ mod tests {
         dbg!(&mesh);
         assert_eq!(root_vert.edges().count(), 4);
     }
+
+    #[test]
+    fn can_iterate_around_boundary_vertex() {
+        let _ = env_logger::try_init();
+        let positions = [
+            Position::new(0.0, 0.0, 0.0),
+            Position::new(1.0, 0.0, 0.0),
+            Position::new(1.0, 1.0, 0.0),
+            Position::new(0.0, 1.0, 0.0),
+            Position::new(-1.0, 1.0, 0.0),
+        ];
+        let mesh = Mesh::from_polygons(&positions, &[[0, 1, 2], [0, 2, 3], [0, 3, 4]]);
+
+        let center = mesh
+            .vertices()
+            .filter(|vert| vert.point().position() == positions[0])
+            .find(|vert| vert.edge().face().is_valid())
+            .unwrap();
+        let edges: Vec<_> = center.edges().collect();
+        assert_eq!(edges.len(), 4);
+        assert!(edges
+            .iter()
+            .all(|edge| edge.vertex().point().handle == center.point().handle));
+        assert_eq!(
+            edges.iter().filter(|edge| !edge.face().is_valid()).count(),
+            1
+        );
+        assert_eq!(center.faces().count(), 3);
+    }
+
+    #[test]
+    fn can_iterate_over_faces_around_vertex() {
+        let _ = env_logger::try_init();
+        let mesh = Mesh::icosphere(1.0, 0);
+        for vert in mesh.vertices() {
+            let mut faces: Vec<_> = vert
+                .faces()
+                .map(|face| {
+                    assert!(face
+                        .vertices()
+                        .any(|v| v.point().handle == vert.point().handle));
+                    face.handle.index()
+                })
+                .collect();
+            faces.sort();
+            faces.dedup();
+            assert_eq!(faces.len(), 5);
+        }
+    }
+
+    #[test]
+    fn can_iterate_over_triangles_of_face() {
+        let _ = env_logger::try_init();
+        let positions: Vec<_> = (0..5)
+            .map(|index| {
+                let angle = index as f32 * std::f32::consts::PI * 0.4;
+                Position::new(angle.cos(), angle.sin(), 0.0)
+            })
+            .collect();
+        let mesh = Mesh::from_polygons(&positions, &[[0, 1, 2, 3, 4]]);
+        let face = mesh.faces().next().unwrap();
+        let vertices: Vec<_> = face.vertices().map(|v| v.handle).collect();
+
+        let triangles: Vec<_> = face
+            .triangles()
+            .map(|(v0, v1, v2)| (v0.handle, v1.handle, v2.handle))
+            .collect();
+        assert_eq!(
+            triangles,
+            vec![
+                (vertices[0], vertices[1], vertices[2]),
+                (vertices[0], vertices[2], vertices[3]),
+                (vertices[0], vertices[3], vertices[4]),
+            ]
+        );
+        let area: f32 = face
+            .triangles()
+            .map(|(v0, v1, v2)| {
+                let (p0, p1, p2) = (
+                    v0.point().position(),
+                    v1.point().position(),
+                    v2.point().position(),
+                );
+                (p1 - p0).cross(&(p2 - p0)).norm() * 0.5
+            })
+            .sum();
+        assert!((area - face.area()).abs() < 1e-5);
+    }
+
+    /// Three triangles around the origin, which lies on the boundary.
+    fn boundary_fan() -> (Mesh, Vec<VertexHandle>) {
+        let positions = [
+            Position::new(0.0, 0.0, 0.0),
+            Position::new(1.0, 0.0, 0.0),
+            Position::new(1.0, 1.0, 0.0),
+            Position::new(0.0, 1.0, 0.0),
+            Position::new(-1.0, 1.0, 0.0),
+        ];
+        let mesh = Mesh::from_polygons(&positions, &[[0, 1, 2], [0, 2, 3], [0, 3, 4]]);
+        let vertices = mesh
+            .vertices()
+            .filter(|vert| vert.point().position() == positions[0])
+            .map(|vert| vert.handle)
+            .collect();
+        (mesh, vertices)
+    }
+
+    #[test]
+    fn boundary_vertex_circulation_does_not_depend_on_start() {
+        let _ = env_logger::try_init();
+        let (mesh, vertices) = boundary_fan();
+        assert_eq!(vertices.len(), 4);
+        let mut expected: Vec<_> = mesh
+            .edges()
+            .filter(|edge| edge.vertex().point().position() == Position::origin())
+            .map(|edge| edge.handle.index())
+            .collect();
+        expected.sort();
+        // Starting in the middle of the fan needs the reversed walk to
+        // reach the edges before the start.
+        for vert in vertices {
+            let mut edges: Vec<_> = mesh
+                .vertex(vert)
+                .edges()
+                .map(|edge| edge.handle.index())
+                .collect();
+            edges.sort();
+            assert_eq!(edges, expected);
+        }
+    }
+
+    #[test]
+    fn face_circulator_skips_boundary_edges() {
+        let _ = env_logger::try_init();
+        let (mesh, vertices) = boundary_fan();
+        for vert in vertices {
+            let mut faces: Vec<_> = mesh
+                .vertex(vert)
+                .faces()
+                .map(|face| {
+                    assert!(face.is_valid());
+                    face.handle.index()
+                })
+                .collect();
+            faces.sort();
+            faces.dedup();
+            assert_eq!(faces.len(), 3);
+        }
+    }
 }
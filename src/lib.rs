@@ -2,20 +2,40 @@
 //! An index based half-edge mesh implementation.
 //!
 
+pub use crate::buffers::*;
 pub use crate::data::*;
+pub use crate::decimate::*;
 pub use crate::elements::*;
 pub use crate::handles::*;
 pub use crate::iterators::*;
 pub use crate::mesh::*;
 pub use crate::proxy::*;
+pub use crate::remesh::*;
+pub use crate::smoothing::*;
+pub use crate::subdivision::*;
 pub use crate::traits::*;
 
+pub mod bevel;
+pub mod bridge;
+pub mod buffers;
 pub mod data;
+pub mod decimate;
 pub mod elements;
+pub mod extrude;
 pub mod handles;
+pub mod holes;
 pub mod kernel;
+pub mod measure;
 pub mod mesh;
+pub mod operators;
+pub mod primitives;
 pub mod proxy;
+pub mod remesh;
+pub mod smoothing;
+pub mod subdivision;
+#[cfg(test)]
+mod test_utils;
 pub mod traits;
+pub mod triangulate;
 //pub mod utils;
 pub mod iterators;
@@ -0,0 +1,176 @@
+//! Geometric measures of a whole mesh.
+//!
+//! Volume, center of mass and inertia are those of the solid enclosed by a
+//! closed mesh of uniform unit density, with faces wound counter-clockwise
+//! when seen from outside. Inverted meshes get a negative volume.
+
+use nalgebra as na;
+
+use crate::data::{BoundingBox, Normal, Position};
+use crate::mesh::Mesh;
+
+type Matrix = na::Matrix3<f32>;
+
+impl Mesh {
+    /// Signed tetrahedra between the origin and the triangles of all faces.
+    fn tetrahedra(&self) -> Vec<(Normal, Normal, Normal)> {
+        let mut tetrahedra = Vec::new();
+        for face in self.faces() {
+            for (v0, v1, v2) in face.triangles() {
+                tetrahedra.push((
+                    v0.point().position().coords,
+                    v1.point().position().coords,
+                    v2.point().position().coords,
+                ));
+            }
+        }
+        tetrahedra
+    }
+
+    /// Smallest axis-aligned box containing all points, or `None` for
+    /// meshes without points.
+    pub fn bounding_box(&self) -> Option<BoundingBox> {
+        let mut points = self.points().map(|point| point.position());
+        let first = points.next()?;
+        Some(points.fold(
+            BoundingBox {
+                min: first,
+                max: first,
+            },
+            |bounds, position| BoundingBox {
+                min: Position::from(bounds.min.coords.zip_map(&position.coords, f32::min)),
+                max: Position::from(bounds.max.coords.zip_map(&position.coords, f32::max)),
+            },
+        ))
+    }
+
+    /// Total area of all faces.
+    pub fn surface_area(&self) -> f32 {
+        self.faces().map(|face| face.area()).sum()
+    }
+
+    /// Signed volume enclosed by the mesh, only meaningful for closed meshes.
+    pub fn volume(&self) -> f32 {
+        self.tetrahedra()
+            .iter()
+            .map(|(a, b, c)| a.dot(&b.cross(c)) / 6.0)
+            .sum()
+    }
+
+    /// Center of mass of the enclosed solid.
+    ///
+    /// Falls back to the center of the surface area for meshes enclosing no
+    /// volume, and returns `None` for meshes without faces.
+    pub fn center_of_mass(&self) -> Option<Position> {
+        let mut moment: Normal = na::zero();
+        let mut volume = 0.0;
+        for (a, b, c) in self.tetrahedra() {
+            let tetrahedron = a.dot(&b.cross(&c)) / 6.0;
+            moment += (a + b + c) * (tetrahedron / 4.0);
+            volume += tetrahedron;
+        }
+        if volume.abs() > f32::EPSILON {
+            return Some(Position::from(moment / volume));
+        }
+
+        let mut moment: Normal = na::zero();
+        let mut area = 0.0;
+        for face in self.faces() {
+            let face_area = face.area();
+            moment += face.centroid().coords * face_area;
+            area += face_area;
+        }
+        if area > f32::EPSILON {
+            Some(Position::from(moment / area))
+        } else {
+            None
+        }
+    }
+
+    /// Inertia tensor of the enclosed solid around its center of mass.
+    ///
+    /// Built from the covariance of the signed tetrahedra (Tonon), so the
+    /// mass equals the volume and the tensor changes sign for inverted
+    /// meshes. Zero for meshes enclosing no volume.
+    pub fn inertia_tensor(&self) -> Matrix {
+        // Covariance of the tetrahedron spanned by the unit vectors.
+        let canonical = Matrix::new(2.0, 1.0, 1.0, 1.0, 2.0, 1.0, 1.0, 1.0, 2.0) / 120.0;
+        let mut covariance = Matrix::zeros();
+        let mut volume = 0.0;
+        for (a, b, c) in self.tetrahedra() {
+            let transform = Matrix::from_columns(&[a, b, c]);
+            let determinant = transform.determinant();
+            covariance += transform * canonical * transform.transpose() * determinant;
+            volume += determinant / 6.0;
+        }
+        if volume.abs() <= f32::EPSILON {
+            return Matrix::zeros();
+        }
+        let center = self
+            .center_of_mass()
+            .map(|center| center.coords)
+            .unwrap_or_else(na::zero);
+        let covariance = covariance - center * center.transpose() * volume;
+        Matrix::identity() * covariance.trace() - covariance
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use nalgebra as na;
+
+    fn assert_close(a: f32, b: f32) {
+        assert!((a - b).abs() < 1e-4, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn can_compute_bounding_box() {
+        let _ = env_logger::try_init();
+        let mesh = Mesh::unit_cube();
+        let bounds = mesh.bounding_box().unwrap();
+        assert_eq!(bounds.min, Position::new(0.0, 0.0, 0.0));
+        assert_eq!(bounds.max, Position::new(1.0, 1.0, 1.0));
+        assert_eq!(bounds.center(), Position::new(0.5, 0.5, 0.5));
+        assert_eq!(bounds.size(), Normal::new(1.0, 1.0, 1.0));
+        assert_eq!(Mesh::default().bounding_box(), None);
+    }
+
+    #[test]
+    fn can_measure_cube() {
+        let _ = env_logger::try_init();
+        let mesh = Mesh::unit_cube();
+        assert_close(mesh.surface_area(), 6.0);
+        assert_close(mesh.volume(), 1.0);
+        let center = mesh.center_of_mass().unwrap();
+        assert!((center - Position::new(0.5, 0.5, 0.5)).norm() < 1e-5);
+
+        let inertia = mesh.inertia_tensor();
+        for axis in 0..3 {
+            assert_close(inertia[(axis, axis)], 1.0 / 6.0);
+        }
+        for (row, column) in [(0, 1), (0, 2), (1, 2)].iter() {
+            assert_close(inertia[(*row, *column)], 0.0);
+            assert_close(inertia[(*column, *row)], 0.0);
+        }
+    }
+
+    #[test]
+    fn can_measure_sphere() {
+        let _ = env_logger::try_init();
+        let sphere = Mesh::icosphere(1.0, 3);
+        let pi = std::f32::consts::PI;
+        assert!((sphere.surface_area() - 4.0 * pi).abs() < 0.1);
+        assert!((sphere.volume() - 4.0 / 3.0 * pi).abs() < 0.05);
+        assert!(sphere.center_of_mass().unwrap().coords.norm() < 1e-4);
+    }
+
+    #[test]
+    fn open_meshes_use_the_surface_center() {
+        let _ = env_logger::try_init();
+        let mesh = Mesh::grid(2.0, 2.0, 2, 2);
+        assert_close(mesh.volume(), 0.0);
+        assert!(mesh.center_of_mass().unwrap().coords.norm() < 1e-6);
+        assert_eq!(mesh.inertia_tensor(), na::Matrix3::zeros());
+    }
+}
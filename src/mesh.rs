@@ -3,7 +3,7 @@ use std::sync::atomic;
 
 use crate::data::Tag;
 use crate::elements::{Face, Point, Vertex};
-use crate::handles::{FaceHandle, HalfEdgeHandle, PointHandle, VertexHandle};
+use crate::handles::{FaceHandle, FullEdgeHandle, HalfEdgeHandle, PointHandle, VertexHandle};
 use crate::kernel::Kernel;
 use crate::proxy::*;
 use crate::traits::*;
@@ -49,41 +49,41 @@ impl Mesh {
             mesh.add(Point::from_position(0.0, 1.0, 1.0)),
         ];
 
-        let front = mesh.add_face([p[0], p[1], p[5], p[4]].as_ref());
-        let back = mesh.add_face([p[2], p[3], p[7], p[6]].as_ref());
+        let front = mesh.add_face([p[0], p[4], p[5], p[1]].as_ref());
+        let back = mesh.add_face([p[2], p[6], p[7], p[3]].as_ref());
 
         let bottom = mesh.add_face(
             [
-                mesh.face(front).root_edge().adjacent().handle,
-                mesh.face(back).root_edge().adjacent().handle,
+                mesh.face(front).root_edge().prev().adjacent().handle,
+                mesh.face(back).root_edge().prev().adjacent().handle,
             ]
             .as_ref(),
         );
 
         let top = mesh.add_face(
             [
-                mesh.face(front).root_edge().next().next().adjacent().handle,
-                mesh.face(back).root_edge().next().next().adjacent().handle,
+                mesh.face(front).root_edge().next().adjacent().handle,
+                mesh.face(back).root_edge().next().adjacent().handle,
             ]
             .as_ref(),
         );
 
         let _right = mesh.add_face(
             [
-                mesh.face(front).root_edge().next().adjacent().handle,
-                mesh.face(bottom).root_edge().prev().adjacent().handle,
-                mesh.face(back).root_edge().prev().adjacent().handle,
-                mesh.face(top).root_edge().next().adjacent().handle,
+                mesh.face(front).root_edge().next().next().adjacent().handle,
+                mesh.face(top).root_edge().prev().adjacent().handle,
+                mesh.face(back).root_edge().adjacent().handle,
+                mesh.face(bottom).root_edge().next().adjacent().handle,
             ]
             .as_ref(),
         );
 
         let _left = mesh.add_face(
             [
-                mesh.face(front).root_edge().prev().adjacent().handle,
-                mesh.face(bottom).root_edge().next().adjacent().handle,
-                mesh.face(back).root_edge().next().adjacent().handle,
-                mesh.face(top).root_edge().prev().adjacent().handle,
+                mesh.face(bottom).root_edge().prev().adjacent().handle,
+                mesh.face(back).root_edge().next().next().adjacent().handle,
+                mesh.face(top).root_edge().next().adjacent().handle,
+                mesh.face(front).root_edge().adjacent().handle,
             ]
             .as_ref(),
         );
@@ -108,7 +108,12 @@ impl Mesh {
         self.kernel
             .face_buffer
             .active_cells()
-            .map(move |(handle, _)| FaceProxy::new(FaceHandle::new(handle as u32), self))
+            .map(move |(offset, face)| {
+                FaceProxy::new(
+                    FaceHandle::with_generation(offset as u32, face.generation()),
+                    self,
+                )
+            })
     }
 
     /// Returns an `EdgeProxy` for the given handle.
@@ -124,7 +129,29 @@ impl Mesh {
         self.kernel
             .edge_buffer
             .active_cells()
-            .map(move |(offset, _)| HalfEdgeProxy::new(HalfEdgeHandle::new(offset as u32), self))
+            .map(move |(offset, edge)| {
+                HalfEdgeProxy::new(
+                    HalfEdgeHandle::with_generation(offset as u32, edge.generation()),
+                    self,
+                )
+            })
+    }
+
+    /// Returns a `FullEdgeProxy` for the given handle.
+    pub fn full_edge(&self, handle: FullEdgeHandle) -> FullEdgeProxy {
+        FullEdgeProxy::new(handle, &self)
+    }
+
+    /// Returns the number of undirected edges.
+    pub fn full_edge_count(&self) -> usize {
+        self.full_edges().count()
+    }
+
+    /// Visits each undirected edge once.
+    pub fn full_edges(&self) -> impl Iterator<Item = FullEdgeProxy> {
+        self.edges()
+            .filter(|edge| edge.full_edge().handle.index() == edge.handle.index())
+            .map(|edge| edge.full_edge())
     }
 
     /// Returns a `VertexProxy` for the given handle.
@@ -140,7 +167,12 @@ impl Mesh {
         self.kernel
             .vertex_buffer
             .active_cells()
-            .map(move |(offset, _)| VertexProxy::new(VertexHandle::new(offset as u32), self))
+            .map(move |(offset, vertex)| {
+                VertexProxy::new(
+                    VertexHandle::with_generation(offset as u32, vertex.generation()),
+                    self,
+                )
+            })
     }
 
     pub fn point(&self, handle: PointHandle) -> PointProxy {
@@ -151,6 +183,18 @@ impl Mesh {
         self.kernel.point_buffer.len() - 1
     }
 
+    pub fn points(&self) -> impl Iterator<Item = PointProxy> {
+        self.kernel
+            .point_buffer
+            .active_cells()
+            .map(move |(offset, point)| {
+                PointProxy::new(
+                    PointHandle::with_generation(offset as u32, point.generation()),
+                    self,
+                )
+            })
+    }
+
     pub fn add<E: Element>(&mut self, element: E) -> E::Handle
     where
         Kernel: AddElement<E>,
@@ -177,6 +221,86 @@ impl Mesh {
     }
 }
 
+impl IsValid for Mesh {
+    /// Checks the connectivity of all active elements.
+    ///
+    /// A mesh is valid when every half-edge is paired with an adjacent
+    /// half-edge pointing back at it, every vertex belongs to a point,
+    /// and the edge loop of every face is closed and assigned to it.
+    /// Half-edges without a face mark the boundary and don't need to
+    /// be connected to a next or previous edge.
+    fn is_valid(&self) -> bool {
+        for edge in self.edges() {
+            let adjacent = edge.adjacent();
+            if adjacent.adjacent().handle.index() != edge.handle.index() {
+                log::warn!("Edge {} is not adjacent to its twin.", edge.handle.index());
+                return false;
+            }
+            let vertex = edge.vertex();
+            if !vertex.is_valid() || !vertex.point().is_valid() {
+                log::warn!(
+                    "Edge {} has an invalid vertex or point.",
+                    edge.handle.index()
+                );
+                return false;
+            }
+            let face = edge.face();
+            if face.is_valid() {
+                let next = edge.next();
+                if next.prev().handle.index() != edge.handle.index()
+                    || next.face().handle.index() != face.handle.index()
+                {
+                    log::warn!("Edge {} is not properly connected.", edge.handle.index());
+                    return false;
+                }
+                if next.vertex().point().handle != adjacent.vertex().point().handle {
+                    log::warn!(
+                        "Edge {} does not end where its next edge starts.",
+                        edge.handle.index()
+                    );
+                    return false;
+                }
+            }
+        }
+
+        let edge_count = self.edge_count();
+        for face in self.faces() {
+            let root_edge = face.root_edge();
+            if !root_edge.is_valid() {
+                log::warn!("Face {} has an invalid root edge.", face.handle.index());
+                return false;
+            }
+            let mut edge = root_edge;
+            let mut loop_length = 0;
+            loop {
+                if edge.face().handle.index() != face.handle.index() {
+                    log::warn!(
+                        "Edge {} in the loop of face {} is assigned to another face.",
+                        edge.handle.index(),
+                        face.handle.index()
+                    );
+                    return false;
+                }
+                loop_length += 1;
+                edge = edge.next();
+                if edge.handle.index() == root_edge.handle.index() {
+                    break;
+                }
+                if loop_length > edge_count {
+                    log::warn!("Edge loop of face {} is not closed.", face.handle.index());
+                    return false;
+                }
+            }
+            if loop_length < 3 {
+                log::warn!("Face {} has less than 3 edges.", face.handle.index());
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
 impl<'a> MakeEdge<(VertexHandle, VertexHandle)> for Mesh {
     fn make_edge(
         &mut self,
@@ -376,6 +500,9 @@ impl<'a> AddFace<(HalfEdgeHandle, &'a [PointHandle], FaceHandle)> for Mesh {
             f0.index()
         );
         assert!(!points.is_empty());
+        if let Some(edge) = self.get(root_edge) {
+            edge.data_mut().face = f0;
+        }
         let mut previous_edge = root_edge;
         for current_point in points {
             let edge_pair = self.make_edge((previous_edge, *current_point, f0));
@@ -419,7 +546,7 @@ impl<'a> AddFace<&[HalfEdgeHandle]> for Mesh {
         let mut last_edge = root_edge;
         for next_edge in edges.iter().skip(1) {
             let last_point = self.edge(last_edge).adjacent().vertex().point().handle;
-            let next_point = self.edge(*next_edge).adjacent().vertex().point().handle;
+            let next_point = self.edge(*next_edge).vertex().point().handle;
             if last_point == next_point {
                 self.edge(last_edge).connect_to(&self.edge(*next_edge));
             } else {
@@ -490,9 +617,11 @@ impl<'a> AddFace<(&[HalfEdgeHandle], &[PointHandle])> for Mesh {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::data::Position;
     use crate::elements::{Face, HalfEdge, Point, Vertex};
     use crate::handles::PointHandle;
     use log::*;
+    use std::collections::HashSet;
 
     #[test]
     fn basic_debug_printing() {
@@ -690,6 +819,75 @@ mod tests {
         assert_eq!(point(&edges[4]), p2);
     }
 
+    #[test]
+    fn unit_cube_is_closed_and_consistently_wound() {
+        let _ = env_logger::try_init();
+        let mesh = Mesh::unit_cube();
+        assert!(mesh.is_valid());
+        assert_eq!(mesh.full_edge_count(), 12);
+        assert_eq!(mesh.full_edges().filter(|e| e.is_boundary()).count(), 0);
+        assert!(mesh.faces().all(|face| face.vertices().count() == 4));
+        let center = Position::new(0.5, 0.5, 0.5);
+        for face in mesh.faces() {
+            let outward = face.centroid() - center;
+            assert!(face.normal().dot(&outward) > 0.0);
+        }
+    }
+
+    #[test]
+    fn can_add_face_from_connected_edges() {
+        let _ = env_logger::try_init();
+        let mut mesh = Mesh::default();
+
+        let p0 = mesh.add(Point::from_position(-1.0, 0.0, 0.0));
+        let p1 = mesh.add(Point::from_position(1.0, 0.0, 0.0));
+        let p2 = mesh.add(Point::from_position(0.0, 1.0, 0.0));
+        let (e0, _) = mesh.make_edge((p0, p1));
+        let (e1, _) = mesh.make_edge((p1, p2));
+
+        // The edges meet at p1, so only the closing edge is added.
+        let f0 = mesh.add_face([e0, e1].as_ref());
+        assert!(mesh.is_valid());
+        assert_eq!(mesh.face(f0).edges().count(), 3);
+        assert_eq!(mesh.edge_count(), 6);
+    }
+
+    #[test]
+    fn add_face_assigns_the_root_edge() {
+        let _ = env_logger::try_init();
+        let mut mesh = Mesh::default();
+
+        let p0 = mesh.add(Point::from_position(-1.0, 0.0, 0.0));
+        let p1 = mesh.add(Point::from_position(1.0, 0.0, 0.0));
+        let p2 = mesh.add(Point::from_position(0.0, 1.0, 0.0));
+        let (e0, _) = mesh.make_edge((p0, p1));
+
+        let f0 = mesh.add_face((e0, [p2].as_ref()));
+        assert!(mesh.is_valid());
+        assert_eq!(mesh.edge(e0).face().handle, f0);
+        assert_eq!(mesh.face(f0).edges().count(), 3);
+    }
+
+    #[test]
+    fn iterated_handles_match_added_handles() {
+        let _ = env_logger::try_init();
+        let mut mesh = Mesh::default();
+
+        let p0 = mesh.add(Point::from_position(-1.0, 0.0, 0.0));
+        let p1 = mesh.add(Point::from_position(1.0, 0.0, 0.0));
+        let p2 = mesh.add(Point::from_position(0.0, 1.0, 0.0));
+        let f0 = mesh.add_face([p0, p1, p2].as_ref());
+
+        // Handles returned by `add` carry a generation, so only handles
+        // with the same generation compare equal to them.
+        assert!(mesh.faces().any(|face| f0 == face.handle));
+        assert!(mesh.points().any(|point| p0 == point.handle));
+        let faces: HashSet<FaceHandle> = mesh.faces().map(|face| face.handle).collect();
+        assert!(faces.contains(&f0));
+        let root_edge = mesh.face(f0).root_edge().handle;
+        assert!(mesh.edges().any(|edge| root_edge == edge.handle));
+    }
+
     #[test]
     fn can_iterate_over_faces() {
         let _ = env_logger::try_init();
@@ -748,4 +946,42 @@ mod tests {
         let face = mesh.add_face(points.as_ref());
         assert_eq!(mesh.face(face).triangles().count(), 2);
     }
+
+    #[test]
+    fn can_iterate_over_full_edges() {
+        let _ = env_logger::try_init();
+        let mut mesh = Mesh::default();
+
+        let p0 = mesh.add(Point::from_position(0.0, 0.0, 0.0));
+        let p1 = mesh.add(Point::from_position(1.0, 0.0, 0.0));
+        let p2 = mesh.add(Point::from_position(0.0, 1.0, 0.0));
+        let p3 = mesh.add(Point::from_position(1.0, 1.0, 0.0));
+
+        let f0 = mesh.add_face([p0, p1, p2].as_ref());
+        assert_eq!(mesh.edge_count(), 6);
+        assert_eq!(mesh.full_edge_count(), 3);
+
+        let shared_edge = mesh.face(f0).root_edge().next().adjacent().handle;
+        let f1 = mesh.add_face((shared_edge, [p3].as_ref()));
+        assert_eq!(mesh.edge_count(), 10);
+        assert_eq!(mesh.full_edge_count(), 5);
+
+        let shared_edge = mesh.edge(shared_edge);
+        let full_edge = shared_edge.full_edge();
+        assert_eq!(full_edge.handle, shared_edge.adjacent().full_edge().handle);
+        assert!(!full_edge.is_boundary());
+
+        let (face0, face1) = full_edge.faces();
+        assert!(face0.handle == f0 || face0.handle == f1);
+        assert!(face1.handle == f0 || face1.handle == f1);
+        assert_ne!(face0.handle, face1.handle);
+
+        let (point0, point1) = full_edge.points();
+        assert!(point0.handle == p1 || point0.handle == p2);
+        assert!(point1.handle == p1 || point1.handle == p2);
+        assert_ne!(point0.handle, point1.handle);
+
+        let boundary_count = mesh.full_edges().filter(|e| e.is_boundary()).count();
+        assert_eq!(boundary_count, 4);
+    }
 }
@@ -0,0 +1,1508 @@
+//! Local operators changing the topology of a mesh in place.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::data::{Normal, Position, VertexAttributes, VertexRemoval};
+use crate::elements::{Face, Point};
+use crate::handles::{FaceHandle, HalfEdgeHandle, PointHandle, VertexHandle};
+use crate::mesh::Mesh;
+use crate::proxy::*;
+use crate::traits::*;
+
+/// A face built by `Mesh::replace_faces`.
+pub(crate) struct Polygon {
+    /// Face to reuse, a new face is added for an invalid handle.
+    pub face: FaceHandle,
+    pub points: Vec<PointHandle>,
+    /// Attributes of the corners, one for each point.
+    pub attributes: Vec<VertexAttributes>,
+}
+
+/// Outgoing half-edges around a vertex, ordered so that each face lies
+/// between an edge and the next one.
+pub(crate) struct Fan {
+    pub edges: Vec<HalfEdgeHandle>,
+    pub closed: bool,
+}
+
+impl Fan {
+    pub(crate) fn new(mesh: &Mesh, edge: HalfEdgeHandle) -> Self {
+        let start = mesh.edge(edge);
+        let limit = mesh.edge_count();
+        let mut first = start;
+        for _ in 0..limit {
+            let twin = first.adjacent();
+            if !twin.face().is_valid() || twin.next().handle.index() == start.handle.index() {
+                break;
+            }
+            first = twin.next();
+        }
+
+        let mut edges = vec![first.handle];
+        let mut current = first;
+        let mut closed = false;
+        while current.face().is_valid() && edges.len() <= limit {
+            let next = current.prev().adjacent();
+            if next.handle.index() == first.handle.index() {
+                closed = true;
+                break;
+            }
+            edges.push(next.handle);
+            current = next;
+        }
+        Fan { edges, closed }
+    }
+
+    /// Number of faces around the vertex, the i-th lying between the i-th
+    /// and the following edge.
+    pub(crate) fn face_count(&self) -> usize {
+        if self.closed {
+            self.edges.len()
+        } else {
+            self.edges.len() - 1
+        }
+    }
+
+    pub(crate) fn next(&self, index: usize) -> usize {
+        (index + 1) % self.edges.len()
+    }
+
+    /// Indices of the edges sharing a face with the edge at `index`.
+    pub(crate) fn neighbors(&self, index: usize) -> Vec<usize> {
+        let count = self.edges.len();
+        let mut neighbors = Vec::new();
+        if index > 0 || self.closed {
+            neighbors.push((index + count - 1) % count);
+        }
+        if index < self.face_count() {
+            neighbors.push(self.next(index));
+        }
+        neighbors
+    }
+}
+
+impl Mesh {
+    /// Checks whether collapsing the edge keeps the mesh manifold.
+    ///
+    /// The collapse is rejected when the points of the edge share a neighbor
+    /// other than the opposite corners of adjacent triangles (the link
+    /// condition), when it would join two separate parts of the boundary,
+    /// or when a triangle would degenerate into a dangling edge.
+    pub fn is_collapse_ok(&self, edge: HalfEdgeHandle) -> bool {
+        let edge = self.edge(edge);
+        if !edge.is_valid() {
+            return false;
+        }
+        let twin = edge.adjacent();
+        if !edge.face().is_valid() && !twin.face().is_valid() {
+            return false;
+        }
+        let (a, b) = (edge.vertex().point().handle, twin.vertex().point().handle);
+        if a.index() == b.index() {
+            return false;
+        }
+
+        let a_edges: Vec<_> = edge.vertex().edges().collect();
+        let b_edges: Vec<_> = twin.vertex().edges().collect();
+        let a_boundary = a_edges.iter().any(|e| !e.face().is_valid());
+        let b_boundary = b_edges.iter().any(|e| !e.face().is_valid());
+        if a_boundary && b_boundary && !edge.is_boundary() {
+            return false;
+        }
+
+        let mut opposite = Vec::new();
+        for side in [edge, twin].iter() {
+            if !side.face().is_valid()
+                || side.next().next().next().handle.index() != side.handle.index()
+            {
+                continue;
+            }
+            if !side.next().adjacent().face().is_valid()
+                && !side.prev().adjacent().face().is_valid()
+            {
+                return false;
+            }
+            opposite.push(side.prev().vertex());
+        }
+        if let [c, d] = opposite.as_slice() {
+            let d = d.point().handle;
+            if c.point().handle == d || c.edges().any(|e| e.adjacent().vertex().point().handle == d)
+            {
+                return false;
+            }
+        }
+
+        let a_neighbors: HashSet<PointHandle> = a_edges
+            .iter()
+            .map(|e| e.adjacent().vertex().point().handle)
+            .collect();
+        let shared_neighbors = b_edges
+            .iter()
+            .map(|e| e.adjacent().vertex().point().handle)
+            .filter(|p| a_neighbors.contains(p))
+            .count();
+        if shared_neighbors != opposite.len() {
+            return false;
+        }
+
+        let (f0, f1) = (edge.face().handle, twin.face().handle);
+        !b_edges.iter().any(|e| {
+            let face = e.face();
+            face.is_valid()
+                && face.handle != f0
+                && face.handle != f1
+                && face.vertices().any(|v| v.point().handle == a)
+        })
+    }
+
+    /// Collapses the edge into the point it starts at.
+    ///
+    /// The point at the end of the edge is removed and all of its vertices
+    /// are moved to the remaining point, which keeps its position. Adjacent
+    /// triangles disappear while larger faces just lose a corner.
+    ///
+    /// Returns a vertex at the remaining point, or `None` if the collapse
+    /// was rejected by `is_collapse_ok`.
+    pub fn collapse_edge(&mut self, edge: HalfEdgeHandle) -> Option<VertexHandle> {
+        if !self.is_collapse_ok(edge) {
+            log::debug!("Unable to collapse edge {}.", edge.index());
+            return None;
+        }
+
+        let (h, t) = {
+            let edge = self.edge(edge);
+            (edge.handle, edge.adjacent().handle)
+        };
+        let a = self.edge(h).vertex().point().handle;
+        let b = self.edge(t).vertex().point().handle;
+        let a_edges: Vec<HalfEdgeHandle> =
+            self.edge(h).vertex().edges().map(|e| e.handle).collect();
+        let b_edges: Vec<HalfEdgeHandle> =
+            self.edge(t).vertex().edges().map(|e| e.handle).collect();
+
+        let mut removed_edges = vec![h, t];
+        let mut removed_faces = Vec::new();
+        for side in [h, t].iter() {
+            let side = self.edge(*side);
+            let face = side.face();
+            if !face.is_valid() {
+                continue;
+            }
+            let (next, prev) = (side.next(), side.prev());
+            if next.next().handle.index() == prev.handle.index() {
+                let (next_twin, prev_twin) = (next.adjacent(), prev.adjacent());
+                let crease = next.full_edge().crease().max(prev.full_edge().crease());
+                if let (Some(n), Some(p)) = (next_twin.element(), prev_twin.element()) {
+                    let mut n = n.data_mut();
+                    let mut p = p.data_mut();
+                    n.adjacent = prev_twin.handle;
+                    n.crease = crease;
+                    p.adjacent = next_twin.handle;
+                    p.crease = crease;
+                }
+                removed_edges.push(next.handle);
+                removed_edges.push(prev.handle);
+                removed_faces.push(face.handle);
+            } else {
+                prev.connect_to(&next);
+                if let Some(mut data) = face.element().map(|f| f.data_mut()) {
+                    if data.root_edge.index() == side.handle.index() {
+                        data.root_edge = next.handle;
+                    }
+                }
+            }
+        }
+
+        for edge in b_edges.iter() {
+            if let Some(vertex) = self.get(self.edge(*edge).vertex().handle) {
+                vertex.data_mut().point = a;
+            }
+        }
+
+        let is_removed = |e: &HalfEdgeHandle| removed_edges.iter().any(|r| r.index() == e.index());
+        let survivor = a_edges
+            .iter()
+            .chain(b_edges.iter())
+            .find(|e| !is_removed(e))
+            .map(|e| self.edge(*e).vertex().handle);
+
+        let removed_vertices: Vec<VertexHandle> = removed_edges
+            .iter()
+            .map(|e| self.edge(*e).vertex().handle)
+            .collect();
+        for face in removed_faces {
+            self.remove(face);
+        }
+        for edge in removed_edges {
+            self.remove(edge);
+        }
+        for vertex in removed_vertices {
+            self.remove(vertex);
+        }
+        self.remove(b);
+
+        survivor
+    }
+
+    /// Splits the edge by inserting a new point at `position`.
+    ///
+    /// Both half-edges keep their start and end at the new point, while
+    /// the new half-edges continue to the old end points. Adjacent triangles
+    /// are split in two by connecting the new point with the opposite corner,
+    /// larger faces just gain a corner. Vertex attributes are interpolated
+    /// along the edge.
+    ///
+    /// Returns the vertex of the new half-edge leaving the new point in the
+    /// face of `edge`.
+    pub fn split_edge(&mut self, edge: HalfEdgeHandle, position: Position) -> VertexHandle {
+        let (h, t) = {
+            let edge = self.edge(edge);
+            (edge.handle, edge.adjacent().handle)
+        };
+        let (a, b) = {
+            let (h, t) = (self.edge(h), self.edge(t));
+            (h.vertex().point().position(), t.vertex().point().position())
+        };
+        let length = (b - a).norm_squared();
+        let along = if length > f32::EPSILON {
+            ((position - a).dot(&(b - a)) / length).clamp(0.0, 1.0)
+        } else {
+            0.5
+        };
+        let crease = self.edge(h).full_edge().crease();
+        // The vertices at the far end of each side, which is the start of
+        // the next edge or, for boundary edges, of the boundary edge leaving
+        // the twin's point. Taken before the twins are relinked to the new
+        // half-edges.
+        let far_end = |side: HalfEdgeProxy| {
+            if side.face().is_valid() {
+                side.next().vertex().handle
+            } else {
+                let fan = Fan::new(self, side.adjacent().handle);
+                let last = fan.edges[fan.edges.len() - 1];
+                self.edge(last).vertex().handle
+            }
+        };
+        let ends = [far_end(self.edge(h)), far_end(self.edge(t))];
+
+        let point = self.add(Point::new(position));
+        let (h2, t2) = self.make_edge((point, point));
+        for (old, new) in [(h, t2), (t, h2)].iter() {
+            if let (Some(o), Some(n)) = (self.get(*old), self.get(*new)) {
+                o.data_mut().adjacent = *new;
+                o.data_mut().crease = crease;
+                n.data_mut().adjacent = *old;
+                n.data_mut().crease = crease;
+            }
+        }
+
+        let mut triangles = Vec::new();
+        // The new point lies at `along` from the start of `h`, which is the
+        // end of `t`.
+        let sides = [(h, h2, ends[0], along), (t, t2, ends[1], 1.0 - along)];
+        for (side, new, end, along) in sides.iter() {
+            let side = self.edge(*side);
+            let new = self.edge(*new);
+            let attrs = {
+                let start = self.vertex_attributes(side.vertex().handle);
+                let end = self.vertex_attributes(*end);
+                VertexAttributes::blend(&[(&start, 1.0 - along), (&end, *along)])
+            };
+            if let Some(vertex) = new.vertex().element() {
+                vertex.data_mut().attrs = attrs;
+            }
+
+            let face = side.face();
+            if !face.is_valid() {
+                continue;
+            }
+            let next = side.next();
+            if let Some(element) = new.element() {
+                element.data_mut().face = face.handle;
+            }
+            side.connect_to(&new);
+            new.connect_to(&next);
+            if next.next().next().handle.index() == side.handle.index() {
+                triangles.push((new.handle, side.prev().handle));
+            }
+        }
+
+        for (from, to) in triangles {
+            let _ = self.connect_corners(from, to);
+        }
+
+        self.edge(h2).vertex().handle
+    }
+
+    /// Checks whether the edge lies between two triangles and can be
+    /// flipped without creating an edge that already exists.
+    pub fn is_flip_ok(&self, edge: HalfEdgeHandle) -> bool {
+        let edge = self.edge(edge);
+        let twin = edge.adjacent();
+        let is_triangle = |e: &HalfEdgeProxy| {
+            e.face().is_valid() && e.next().next().next().handle.index() == e.handle.index()
+        };
+        if !edge.is_valid() || !is_triangle(&edge) || !is_triangle(&twin) {
+            return false;
+        }
+        let c = edge.prev().vertex();
+        let d = twin.prev().vertex().point().handle;
+        if c.point().handle == d {
+            return false;
+        }
+        let connected = c.edges().any(|e| e.adjacent().vertex().point().handle == d);
+        let valence = |v: VertexProxy| v.edges().count();
+        !connected && valence(edge.vertex()) > 3 && valence(twin.vertex()) > 3
+    }
+
+    /// Replaces the edge between two triangles by the other diagonal of
+    /// the quad they form.
+    ///
+    /// Returns `false` if the flip was rejected by `is_flip_ok`.
+    pub fn flip_edge(&mut self, edge: HalfEdgeHandle) -> bool {
+        if !self.is_flip_ok(edge) {
+            log::debug!("Unable to flip edge {}.", edge.index());
+            return false;
+        }
+        let h = self.edge(edge);
+        let t = h.adjacent();
+        let (h_next, h_prev) = (h.next(), h.prev());
+        let (t_next, t_prev) = (t.next(), t.prev());
+        let (f, g) = (h.face(), t.face());
+
+        let c = self.vertex(h_prev.vertex().handle);
+        let d = self.vertex(t_prev.vertex().handle);
+        for (side, corner) in [(h, d), (t, c)].iter() {
+            if let (Some(vertex), Some(data)) = (side.vertex().element(), corner.data()) {
+                let mut vertex = vertex.data_mut();
+                vertex.point = data.point;
+                vertex.attrs = data.attrs.clone();
+            }
+            if let Some(element) = side.element() {
+                element.data_mut().crease = 0.0;
+            }
+        }
+
+        h_prev.connect_to(&t_next);
+        t_next.connect_to(&h);
+        h.connect_to(&h_prev);
+        t_prev.connect_to(&h_next);
+        h_next.connect_to(&t);
+        t.connect_to(&t_prev);
+        if let (Some(t_next), Some(h_next)) = (t_next.element(), h_next.element()) {
+            t_next.data_mut().face = f.handle;
+            h_next.data_mut().face = g.handle;
+        }
+        if let (Some(f), Some(g)) = (f.element(), g.element()) {
+            f.data_mut().root_edge = h.handle;
+            g.data_mut().root_edge = t.handle;
+        }
+        true
+    }
+
+    pub(crate) fn vertex_attributes(&self, vertex: VertexHandle) -> VertexAttributes {
+        self.vertex(vertex)
+            .data()
+            .map(|data| data.attrs.clone())
+            .unwrap_or_default()
+    }
+
+    /// Splits a face in two by connecting the start points of two of its
+    /// half-edges.
+    ///
+    /// The loop starting at `from` stays with the face, the loop starting at
+    /// `to` gets a new face. Returns the new half-edge ending at the start of
+    /// `from`, together with the new face.
+    pub(crate) fn connect_corners(
+        &mut self,
+        from: HalfEdgeHandle,
+        to: HalfEdgeHandle,
+    ) -> (HalfEdgeHandle, FaceHandle) {
+        let (p0, p1, a0, a1, face) = {
+            let (from, to) = (self.edge(from), self.edge(to));
+            (
+                from.vertex().point().handle,
+                to.vertex().point().handle,
+                self.vertex_attributes(from.vertex().handle),
+                self.vertex_attributes(to.vertex().handle),
+                from.face().handle,
+            )
+        };
+        let (d0, d1) = self.make_edge((p1, p0));
+        let new_face = self.add(Face::new(to));
+        for (edge, attrs, face) in [(d0, a1, face), (d1, a0, new_face)].iter() {
+            let edge = self.edge(*edge);
+            if let (Some(e), Some(v)) = (edge.element(), edge.vertex().element()) {
+                e.data_mut().face = *face;
+                v.data_mut().attrs = attrs.clone();
+            }
+        }
+
+        let (from, to) = (self.edge(from), self.edge(to));
+        let (before_from, before_to) = (from.prev(), to.prev());
+        let (d0, d1) = (self.edge(d0), self.edge(d1));
+        before_to.connect_to(&d0);
+        d0.connect_to(&from);
+        before_from.connect_to(&d1);
+        d1.connect_to(&to);
+
+        if let Some(face) = from.face().element() {
+            face.data_mut().root_edge = from.handle;
+        }
+        self.assign_face_to_loop(to.handle, new_face);
+        (d0.handle, new_face)
+    }
+
+    /// Merges the two faces on either side of the edge into one by
+    /// removing the edge.
+    ///
+    /// The face of `edge` is kept and returned, the other one is removed.
+    /// Faces sharing more than this edge aren't joined, as that would leave
+    /// dangling edges behind.
+    pub fn join_faces(&mut self, edge: HalfEdgeHandle) -> Option<FaceHandle> {
+        let h = self.edge(edge);
+        let t = h.adjacent();
+        let (f, g) = (h.face(), t.face());
+        if !h.is_valid() || !f.is_valid() || !g.is_valid() || f.handle.index() == g.handle.index() {
+            log::warn!("Unable to join faces at edge {}.", edge.index());
+            return None;
+        }
+        let shared = f
+            .edges()
+            .filter(|e| e.adjacent().face().handle.index() == g.handle.index())
+            .count();
+        if shared > 1 {
+            log::warn!(
+                "Faces {} and {} share more than one edge.",
+                f.handle.index(),
+                g.handle.index()
+            );
+            return None;
+        }
+
+        let (h_prev, h_next) = (h.prev(), h.next());
+        let (t_prev, t_next) = (t.prev(), t.next());
+        h_prev.connect_to(&t_next);
+        t_prev.connect_to(&h_next);
+        if let Some(face) = f.element() {
+            face.data_mut().root_edge = h_prev.handle;
+        }
+        let (face, removed) = (f.handle, g.handle);
+        let dead = [(h.handle, h.vertex().handle), (t.handle, t.vertex().handle)];
+        self.assign_face_to_loop(h_prev.handle, face);
+        self.remove(removed);
+        for (edge, vertex) in dead.iter() {
+            self.remove(*edge);
+            self.remove(*vertex);
+        }
+        Some(face)
+    }
+
+    /// Removes a vertex together with its incident edges.
+    ///
+    /// Faces around the vertex are either merged into a single face, which
+    /// is returned, or removed. Edges, vertices and points left without
+    /// any face are removed as well.
+    pub fn remove_vertex(
+        &mut self,
+        vertex: VertexHandle,
+        mode: VertexRemoval,
+    ) -> Option<FaceHandle> {
+        let vertex = self.vertex(vertex);
+        if !vertex.is_valid() || !vertex.edge().is_valid() {
+            log::warn!("Unable to remove invalid vertex {}.", vertex.handle.index());
+            return None;
+        }
+        let point = vertex.point().handle;
+        let fan = Fan::new(self, vertex.edge().handle);
+        let faces: Vec<_> = (0..fan.face_count())
+            .map(|index| self.edge(fan.edges[index]).face().handle)
+            .collect();
+        if faces.is_empty() {
+            log::warn!(
+                "Vertex {} doesn't belong to any face.",
+                vertex.handle.index()
+            );
+            return None;
+        }
+
+        match mode {
+            VertexRemoval::Delete => {
+                self.delete_faces(&faces, true);
+                None
+            }
+            VertexRemoval::Dissolve => {
+                // The corners of each face except the removed one, the last
+                // corner of a face being the first of the next.
+                let mut points = Vec::new();
+                let mut attributes = Vec::new();
+                for index in 0..faces.len() {
+                    let start = self.edge(fan.edges[index]);
+                    let mut edge = start.next();
+                    while edge.handle.index() != start.handle.index() && edge.is_valid() {
+                        let corner = edge.vertex();
+                        if points.last() != Some(&corner.point().handle) {
+                            points.push(corner.point().handle);
+                            attributes
+                                .push(corner.data().map(|d| d.attrs.clone()).unwrap_or_default());
+                        }
+                        edge = edge.next();
+                    }
+                }
+                if fan.closed && points.len() > 1 && points.first() == points.last() {
+                    points.pop();
+                    attributes.pop();
+                }
+                let distinct: HashSet<_> = points.iter().map(|p| p.index()).collect();
+                if points.len() < 3
+                    || distinct.len() != points.len()
+                    || distinct.contains(&point.index())
+                {
+                    log::warn!(
+                        "Dissolving vertex {} would leave a degenerate face.",
+                        vertex.handle.index()
+                    );
+                    return None;
+                }
+                let polygon = Polygon {
+                    face: faces[0],
+                    points,
+                    attributes,
+                };
+                self.replace_faces(&faces, &[polygon]).pop()
+            }
+        }
+    }
+
+    /// Replaces a face by a fan of triangles around a new point at the
+    /// average of its corners.
+    ///
+    /// Returns the triangles, starting with the face itself.
+    pub fn poke_face(&mut self, face: FaceHandle) -> Vec<FaceHandle> {
+        let corners: Vec<_> = self
+            .face(face)
+            .vertices()
+            .map(|vert| vert.point().position().coords)
+            .collect();
+        if corners.is_empty() {
+            log::warn!("Unable to poke invalid face {}.", face.index());
+            return Vec::new();
+        }
+        let center = corners.iter().sum::<Normal>() / corners.len() as f32;
+        self.poke_face_at(face, Position::from(center))
+    }
+
+    /// Replaces a face by a fan of triangles around a new point at
+    /// `position`.
+    ///
+    /// The attributes of the new corners are the average of the corners of
+    /// the face. Returns the triangles, starting with the face itself.
+    pub fn poke_face_at(&mut self, face: FaceHandle, position: Position) -> Vec<FaceHandle> {
+        let corners: Vec<_> = self
+            .face(face)
+            .vertices()
+            .map(|vert| (vert.point().handle, self.vertex_attributes(vert.handle)))
+            .collect();
+        if corners.len() < 3 {
+            log::warn!("Unable to poke invalid face {}.", face.index());
+            return Vec::new();
+        }
+        let weight = 1.0 / corners.len() as f32;
+        let weighted: Vec<_> = corners.iter().map(|(_, attrs)| (attrs, weight)).collect();
+        let center_attrs = VertexAttributes::blend(&weighted);
+        let center = self.add(Point::new(position));
+
+        let count = corners.len();
+        let polygons: Vec<_> = (0..count)
+            .map(|index| {
+                let (start, ref start_attrs) = corners[index];
+                let (end, ref end_attrs) = corners[(index + 1) % count];
+                Polygon {
+                    face: if index == 0 {
+                        face
+                    } else {
+                        FaceHandle::default()
+                    },
+                    points: vec![start, end, center],
+                    attributes: vec![start_attrs.clone(), end_attrs.clone(), center_attrs.clone()],
+                }
+            })
+            .collect();
+        self.replace_faces(&[face], &polygons)
+    }
+
+    /// Pokes every face of the mesh at the average of its corners.
+    ///
+    /// Returns the number of faces added.
+    pub fn poke_faces(&mut self) -> usize {
+        let faces: Vec<_> = self.faces().map(|face| face.handle).collect();
+        faces
+            .into_iter()
+            .map(|face| self.poke_face(face).len().saturating_sub(1))
+            .sum()
+    }
+
+    /// Sets the face of every half-edge in the loop starting at `edge`.
+    pub(crate) fn assign_face_to_loop(&self, edge: HalfEdgeHandle, face: FaceHandle) {
+        let start = self.edge(edge);
+        let mut edge = start;
+        loop {
+            if let Some(element) = edge.element() {
+                element.data_mut().face = face;
+            }
+            edge = edge.next();
+            if edge.handle.index() == start.handle.index() || !edge.is_valid() {
+                break;
+            }
+        }
+    }
+
+    /// The half-edge of a face starting at the given vertex, or at another
+    /// vertex of the same point.
+    fn corner_of(&self, face: FaceHandle, vertex: VertexHandle) -> Option<HalfEdgeHandle> {
+        let vertex = self.vertex(vertex);
+        if !vertex.is_valid() {
+            return None;
+        }
+        let point = vertex.point().handle;
+        let edges: Vec<_> = self.face(face).edges().collect();
+        edges
+            .iter()
+            .find(|edge| edge.vertex().handle.index() == vertex.handle.index())
+            .or_else(|| {
+                edges
+                    .iter()
+                    .find(|edge| edge.vertex().point().handle == point)
+            })
+            .map(|edge| edge.handle)
+    }
+
+    /// Splits a face in two by a new edge between two of its corners, which
+    /// must not be neighbors.
+    ///
+    /// Vertices of other faces at the same points select the corners as
+    /// well. The part of the face from `v0` up to `v1` keeps the face, the
+    /// rest gets a new face which is returned.
+    pub fn split_face(
+        &mut self,
+        face: FaceHandle,
+        v0: VertexHandle,
+        v1: VertexHandle,
+    ) -> Option<FaceHandle> {
+        if !self.face(face).is_valid() {
+            log::warn!("Unable to split invalid face {}.", face.index());
+            return None;
+        }
+        let (from, to) = match (self.corner_of(face, v0), self.corner_of(face, v1)) {
+            (Some(from), Some(to)) => (self.edge(from), self.edge(to)),
+            _ => {
+                log::warn!("Vertices are not corners of face {}.", face.index());
+                return None;
+            }
+        };
+        let is_next =
+            |a: &HalfEdgeProxy, b: &HalfEdgeProxy| a.next().handle.index() == b.handle.index();
+        if from.handle.index() == to.handle.index() || is_next(&from, &to) || is_next(&to, &from) {
+            log::warn!(
+                "Unable to split face {} between neighboring corners.",
+                face.index()
+            );
+            return None;
+        }
+        let (from, to) = (from.handle, to.handle);
+        Some(self.connect_corners(from, to).1)
+    }
+
+    /// Turns the edges of a face into a boundary loop, keeping the face
+    /// element without any edges.
+    fn unlink_face(&self, face: FaceHandle) -> Vec<HalfEdgeHandle> {
+        let edges: Vec<_> = self.face(face).edges().map(|edge| edge.handle).collect();
+        for edge in edges.iter() {
+            if let Some(element) = self.get(*edge) {
+                let mut data = element.data_mut();
+                data.face = FaceHandle::default();
+                data.next = HalfEdgeHandle::default();
+                data.prev = HalfEdgeHandle::default();
+            }
+        }
+        edges
+    }
+
+    /// Turns the edges of a face into a boundary loop, removing the face.
+    pub(crate) fn open_face(&mut self, face: FaceHandle) -> Vec<HalfEdgeHandle> {
+        let edges = self.unlink_face(face);
+        self.remove(face);
+        edges
+    }
+
+    /// Replaces a patch of faces by new polygons.
+    ///
+    /// Edges on the border of the patch are reused by polygons running
+    /// along them, so the new faces stay connected to the rest of the mesh.
+    /// Edges, vertices and points of the patch which end up unused are
+    /// removed. Returns the faces in the order of the polygons.
+    pub(crate) fn replace_faces(
+        &mut self,
+        faces: &[FaceHandle],
+        polygons: &[Polygon],
+    ) -> Vec<FaceHandle> {
+        self.rebuild_faces(faces, polygons, &[], true)
+    }
+
+    /// Removes faces and edges along with the edges and vertices which end
+    /// up without a face on either side.
+    ///
+    /// Points left without vertices are only removed with `remove_points`.
+    fn delete_elements(
+        &mut self,
+        faces: &[FaceHandle],
+        edges: &[HalfEdgeHandle],
+        remove_points: bool,
+    ) {
+        let mut faces = faces.to_vec();
+        for edge in edges {
+            let edge = self.edge(*edge);
+            if !edge.is_valid() {
+                continue;
+            }
+            for face in [edge.face(), edge.adjacent().face()].iter() {
+                if face.is_valid() && !faces.iter().any(|f| f.index() == face.handle.index()) {
+                    faces.push(face.handle);
+                }
+            }
+        }
+        self.rebuild_faces(&faces, &[], edges, remove_points);
+    }
+
+    /// Removes faces along with the edges, vertices and optionally points
+    /// left without a face.
+    ///
+    /// Edges between a removed and a remaining face become boundary edges.
+    pub fn delete_faces(&mut self, faces: &[FaceHandle], remove_points: bool) {
+        self.delete_elements(faces, &[], remove_points);
+    }
+
+    /// Removes edges together with the faces on either side of them, along
+    /// with the edges, vertices and optionally points left without a face.
+    pub fn delete_edges(&mut self, edges: &[HalfEdgeHandle], remove_points: bool) {
+        self.delete_elements(&[], edges, remove_points);
+    }
+
+    /// Replaces faces by polygons, also considering the given `edges` for
+    /// reuse or removal.
+    fn rebuild_faces(
+        &mut self,
+        faces: &[FaceHandle],
+        polygons: &[Polygon],
+        edges: &[HalfEdgeHandle],
+        remove_points: bool,
+    ) -> Vec<FaceHandle> {
+        let reused: HashSet<_> = polygons
+            .iter()
+            .map(|polygon| polygon.face.index())
+            .collect();
+        let mut points = HashSet::new();
+        let mut candidates = Vec::new();
+        for edge in edges {
+            let edge = self.edge(*edge);
+            if edge.is_valid() {
+                points.insert(edge.vertex().point().handle);
+                points.insert(edge.adjacent().vertex().point().handle);
+                candidates.push(edge.handle);
+                candidates.push(edge.adjacent().handle);
+            }
+        }
+        for face in faces {
+            if !self.face(*face).is_valid() {
+                continue;
+            }
+            for edge in self.face(*face).edges() {
+                points.insert(edge.vertex().point().handle);
+                candidates.push(edge.adjacent().handle);
+            }
+            candidates.extend(self.unlink_face(*face));
+            if !reused.contains(&face.index()) {
+                self.remove(*face);
+            }
+        }
+
+        // Unused half-edges by their start and end point.
+        let mut available: HashMap<(PointHandle, PointHandle), Vec<HalfEdgeHandle>> =
+            HashMap::new();
+        for edge in candidates.iter() {
+            let edge = self.edge(*edge);
+            if !edge.face().is_valid() {
+                let key = (
+                    edge.vertex().point().handle,
+                    edge.adjacent().vertex().point().handle,
+                );
+                let edges = available.entry(key).or_default();
+                if !edges.iter().any(|e| e.index() == edge.handle.index()) {
+                    edges.push(edge.handle);
+                }
+            }
+        }
+
+        let mut result = Vec::with_capacity(polygons.len());
+        for polygon in polygons {
+            let face = if self.face(polygon.face).is_valid() {
+                polygon.face
+            } else {
+                self.add(Face::default())
+            };
+            let count = polygon.points.len();
+            let mut edges = Vec::with_capacity(count);
+            for index in 0..count {
+                let key = (polygon.points[index], polygon.points[(index + 1) % count]);
+                let edge = match available.get_mut(&key).and_then(|edges| edges.pop()) {
+                    Some(edge) => edge,
+                    None => {
+                        let (edge, twin) = self.make_edge(key);
+                        available.entry((key.1, key.0)).or_default().push(twin);
+                        candidates.push(twin);
+                        edge
+                    }
+                };
+                let attrs = polygon.attributes[index].clone();
+                if let Some(vertex) = self.edge(edge).vertex().element() {
+                    vertex.data_mut().attrs = attrs;
+                }
+                if let Some(element) = self.get(edge) {
+                    element.data_mut().face = face;
+                }
+                edges.push(edge);
+            }
+            for index in 0..count {
+                let next = self.edge(edges[(index + 1) % count]);
+                self.edge(edges[index]).connect_to(&next);
+            }
+            if let Some(element) = self.get(face) {
+                element.data_mut().root_edge = edges[0];
+            }
+            result.push(face);
+        }
+
+        // Edges without a face on either side are left over from the patch.
+        let mut removed = HashSet::new();
+        for edge in candidates {
+            let edge = self.edge(edge);
+            let twin = edge.adjacent();
+            if !edge.is_valid() || removed.contains(&edge.handle.index()) {
+                continue;
+            }
+            if !edge.face().is_valid() && !twin.face().is_valid() {
+                removed.insert(edge.handle.index());
+                removed.insert(twin.handle.index());
+                let dead = [
+                    (edge.handle, edge.vertex().handle),
+                    (twin.handle, twin.vertex().handle),
+                ];
+                for (edge, vertex) in dead.iter() {
+                    self.remove(*edge);
+                    self.remove(*vertex);
+                }
+            }
+        }
+        if remove_points {
+            let used: HashSet<_> = self
+                .vertices()
+                .map(|vertex| vertex.point().handle.index())
+                .collect();
+            for point in points {
+                if !used.contains(&point.index()) {
+                    self.remove(point);
+                }
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn can_collapse_edge_of_icosahedron() {
+        let _ = env_logger::try_init();
+        let mut mesh = Mesh::icosphere(1.0, 0);
+
+        let edge = mesh.edges().next().unwrap().handle;
+        let point = mesh.edge(edge).vertex().point().handle;
+        let vert = mesh.collapse_edge(edge).unwrap();
+        assert!(mesh.is_valid());
+        assert_eq!(mesh.vertex(vert).point().handle, point);
+        assert_eq!(mesh.face_count(), 18);
+        assert_eq!(mesh.point_count(), 11);
+        assert_eq!(mesh.full_edge_count(), 27);
+        assert_eq!(mesh.vertex(vert).edges().count(), 6);
+    }
+
+    #[test]
+    fn can_collapse_edge_of_quads() {
+        let _ = env_logger::try_init();
+        let mut mesh = Mesh::grid(1.0, 1.0, 2, 2);
+
+        let edge = mesh
+            .edges()
+            .find(|e| e.vertex().point().position() == Position::origin())
+            .unwrap()
+            .handle;
+        assert!(mesh.collapse_edge(edge).is_some());
+        assert!(mesh.is_valid());
+        assert_eq!(mesh.face_count(), 4);
+        assert_eq!(mesh.point_count(), 8);
+        assert_eq!(
+            mesh.faces().filter(|f| f.vertices().count() == 3).count(),
+            2
+        );
+        assert_eq!(mesh.full_edges().filter(|e| e.is_boundary()).count(), 8);
+    }
+
+    #[test]
+    fn can_collapse_boundary_edge() {
+        let _ = env_logger::try_init();
+        let positions = [
+            Position::new(0.0, 0.0, 0.0),
+            Position::new(1.0, 0.0, 0.0),
+            Position::new(1.0, 1.0, 0.0),
+            Position::new(0.0, 1.0, 0.0),
+            Position::new(-1.0, 1.0, 0.0),
+        ];
+        let mut mesh = Mesh::from_polygons(&positions, &[[0, 1, 2], [0, 2, 3], [0, 3, 4]]);
+
+        let edge = mesh
+            .edges()
+            .find(|e| {
+                e.face().is_valid()
+                    && e.vertex().point().position() == positions[0]
+                    && e.adjacent().vertex().point().position() == positions[1]
+            })
+            .unwrap()
+            .handle;
+        let vert = mesh.collapse_edge(edge).unwrap();
+        assert!(mesh.is_valid());
+        assert_eq!(mesh.face_count(), 2);
+        assert_eq!(mesh.point_count(), 4);
+        assert_eq!(mesh.full_edges().filter(|e| e.is_boundary()).count(), 4);
+        assert_eq!(mesh.vertex(vert).point().position(), positions[0]);
+        assert_eq!(mesh.vertex(vert).edges().count(), 3);
+        assert_eq!(mesh.vertex(vert).faces().count(), 2);
+    }
+
+    #[test]
+    fn rejects_collapses_breaking_manifoldness() {
+        let _ = env_logger::try_init();
+        let positions = [
+            Position::new(0.0, 0.0, 0.0),
+            Position::new(1.0, 0.0, 0.0),
+            Position::new(0.0, 1.0, 0.0),
+            Position::new(0.0, 0.0, 1.0),
+        ];
+        let tetrahedron =
+            Mesh::from_polygons(&positions, &[[0, 2, 1], [0, 1, 3], [1, 2, 3], [0, 3, 2]]);
+        assert!(tetrahedron
+            .edges()
+            .all(|e| !tetrahedron.is_collapse_ok(e.handle)));
+
+        let triangle = Mesh::from_polygons(&positions, &[[0, 1, 2]]);
+        assert!(triangle.edges().all(|e| !triangle.is_collapse_ok(e.handle)));
+
+        let mut strip = Mesh::grid(1.0, 1.0, 1, 2);
+        let edge = strip
+            .full_edges()
+            .find(|e| !e.is_boundary())
+            .unwrap()
+            .half_edge()
+            .handle;
+        assert!(strip.collapse_edge(edge).is_none());
+        assert!(strip.is_valid());
+        assert_eq!(strip.face_count(), 2);
+    }
+
+    #[test]
+    fn can_split_edges() {
+        let _ = env_logger::try_init();
+        let mut mesh = Mesh::icosphere(1.0, 0);
+        let edge = mesh.edges().next().unwrap();
+        let (a, b) = (
+            edge.vertex().point().position(),
+            edge.adjacent().vertex().point().position(),
+        );
+        let edge = edge.handle;
+        let midpoint = Position::from((a.coords + b.coords) * 0.5);
+        let vert = mesh.split_edge(edge, midpoint);
+        assert!(mesh.is_valid());
+        assert_eq!(mesh.face_count(), 22);
+        assert_eq!(mesh.point_count(), 13);
+        assert_eq!(mesh.vertex(vert).point().position(), midpoint);
+        assert_eq!(mesh.vertex(vert).edges().count(), 4);
+        assert!(mesh.faces().all(|f| f.vertices().count() == 3));
+
+        let mut quad = Mesh::grid(1.0, 1.0, 1, 1);
+        let edge = quad.edges().find(|e| e.face().is_valid()).unwrap().handle;
+        let _ = quad.split_edge(edge, Position::origin());
+        assert!(quad.is_valid());
+        assert_eq!(quad.face_count(), 1);
+        assert!(quad.faces().all(|f| f.vertices().count() == 5));
+        assert_eq!(quad.full_edges().filter(|e| e.is_boundary()).count(), 5);
+    }
+
+    #[test]
+    fn split_edge_interpolates_attributes() {
+        let _ = env_logger::try_init();
+        let positions = [
+            Position::new(0.0, 0.0, 0.0),
+            Position::new(1.0, 0.0, 0.0),
+            Position::new(0.0, 1.0, 0.0),
+        ];
+        let mut mesh = Mesh::from_polygons(&positions, &[[0, 1, 2]]);
+        for vert in mesh.vertices() {
+            let x = vert.point().position().x;
+            if let Some(mut data) = vert.data_mut() {
+                data.attrs.color = Color::new(x, 0.0, 0.0, 1.0);
+            }
+        }
+        let edge = mesh
+            .edges()
+            .find(|e| e.face().is_valid() && e.vertex().point().position() == positions[0])
+            .unwrap()
+            .handle;
+        let vert = mesh.split_edge(edge, Position::new(0.25, 0.0, 0.0));
+        assert!(mesh.is_valid());
+        assert_eq!(mesh.face_count(), 2);
+        assert_eq!(mesh.full_edges().filter(|e| e.is_boundary()).count(), 4);
+        let color = mesh
+            .vertex(vert)
+            .data()
+            .map(|data| data.attrs.color)
+            .unwrap();
+        assert!((color.x - 0.25).abs() < 1e-6);
+    }
+
+    #[test]
+    fn split_edge_interpolates_boundary_attributes() {
+        let _ = env_logger::try_init();
+        let positions = [
+            Position::new(0.0, 0.0, 0.0),
+            Position::new(1.0, 0.0, 0.0),
+            Position::new(0.0, 1.0, 0.0),
+        ];
+        let mut mesh = Mesh::from_polygons(&positions, &[[0, 1, 2]]);
+        // Texture coordinates stored in the color, differing between the
+        // face and the boundary side of each point.
+        for vert in mesh.vertices() {
+            let x = vert.point().position().x;
+            let v = if vert.edge().face().is_valid() {
+                0.0
+            } else {
+                1.0
+            };
+            if let Some(mut data) = vert.data_mut() {
+                data.attrs.color = Color::new(x, v, 0.0, 1.0);
+            }
+        }
+        let edge = mesh
+            .edges()
+            .find(|e| !e.face().is_valid() && e.vertex().point().position() == positions[1])
+            .unwrap()
+            .handle;
+        let _ = mesh.split_edge(edge, Position::new(0.25, 0.0, 0.0));
+        assert!(mesh.is_valid());
+        let split = mesh
+            .vertices()
+            .find(|v| !v.edge().face().is_valid() && v.point().position().x == 0.25)
+            .unwrap();
+        let color = split.data().map(|data| data.attrs.color).unwrap();
+        assert!((color.x - 0.25).abs() < 1e-6);
+        assert!((color.y - 1.0).abs() < 1e-6);
+        let inner = mesh
+            .vertices()
+            .find(|v| v.edge().face().is_valid() && v.point().position().x == 0.25)
+            .unwrap();
+        let color = inner.data().map(|data| data.attrs.color).unwrap();
+        assert!((color.x - 0.25).abs() < 1e-6);
+        assert!(color.y.abs() < 1e-6);
+    }
+
+    #[test]
+    fn can_flip_edges() {
+        let _ = env_logger::try_init();
+        let positions = [
+            Position::new(0.0, 0.0, 0.0),
+            Position::new(1.0, 0.0, 0.0),
+            Position::new(1.0, 1.0, 0.0),
+            Position::new(0.0, 1.0, 0.0),
+        ];
+        let mut mesh = Mesh::from_polygons(&positions, &[[0, 1, 2], [0, 2, 3]]);
+        let edge = mesh
+            .full_edges()
+            .find(|e| !e.is_boundary())
+            .unwrap()
+            .half_edge()
+            .handle;
+        // Points on the boundary only have valence 2 and 3.
+        assert!(!mesh.flip_edge(edge));
+
+        let mut mesh = Mesh::icosphere(1.0, 0);
+        let edge = mesh.edges().next().unwrap().handle;
+        let (p0, p1) = (
+            mesh.edge(edge).vertex().point().handle,
+            mesh.edge(edge).adjacent().vertex().point().handle,
+        );
+        assert!(mesh.flip_edge(edge));
+        assert!(mesh.is_valid());
+        assert_eq!(mesh.face_count(), 20);
+        assert!(mesh.faces().all(|f| f.vertices().count() == 3));
+        let flipped = mesh.edge(edge);
+        let (q0, q1) = (
+            flipped.vertex().point().handle,
+            flipped.adjacent().vertex().point().handle,
+        );
+        assert!(q0 != p0 && q0 != p1 && q1 != p0 && q1 != p1);
+        assert_eq!(flipped.vertex().edges().count(), 6);
+    }
+
+    #[test]
+    fn rejects_flips_breaking_manifoldness() {
+        let _ = env_logger::try_init();
+        let grid = Mesh::grid(1.0, 1.0, 2, 2);
+        assert!(grid.edges().all(|e| !grid.is_flip_ok(e.handle)));
+
+        let positions = [
+            Position::new(1.0, 0.0, 0.0),
+            Position::new(-1.0, 0.0, 0.0),
+            Position::new(0.0, 1.0, 0.0),
+            Position::new(0.0, -1.0, 0.0),
+            Position::new(0.0, 0.0, 1.0),
+            Position::new(0.0, 0.0, -1.0),
+        ];
+        let polygons = [
+            [0, 2, 4],
+            [2, 1, 4],
+            [1, 3, 4],
+            [3, 0, 4],
+            [2, 0, 5],
+            [1, 2, 5],
+            [3, 1, 5],
+            [0, 3, 5],
+        ];
+        let mut octahedron = Mesh::from_polygons(&positions, &polygons);
+        assert!(octahedron.edges().all(|e| octahedron.is_flip_ok(e.handle)));
+        let edge = octahedron
+            .edges()
+            .find(|e| {
+                e.vertex().point().position() == positions[0]
+                    && e.adjacent().vertex().point().position() == positions[2]
+            })
+            .unwrap()
+            .handle;
+        assert!(octahedron.flip_edge(edge));
+        assert!(octahedron.is_valid());
+        // Both ends of the flipped edge are left with valence 3.
+        for position in [positions[0], positions[2]].iter() {
+            assert!(octahedron
+                .edges()
+                .filter(|e| e.vertex().point().position() == *position)
+                .all(|e| !octahedron.is_flip_ok(e.handle)));
+        }
+    }
+
+    #[test]
+    fn can_split_faces() {
+        let _ = env_logger::try_init();
+        let positions: Vec<_> = (0..6)
+            .map(|index| {
+                let angle = index as f32 * std::f32::consts::PI / 3.0;
+                Position::new(angle.cos(), 0.0, angle.sin())
+            })
+            .collect();
+        let mut mesh = Mesh::from_polygons(&positions, &[[0, 1, 2, 3, 4, 5]]);
+        let face = mesh.faces().next().unwrap().handle;
+        let corners: Vec<_> = mesh.face(face).vertices().map(|v| v.handle).collect();
+
+        let new_face = mesh.split_face(face, corners[0], corners[3]).unwrap();
+        assert!(mesh.is_valid());
+        assert_eq!(mesh.face_count(), 2);
+        assert_eq!(mesh.face(face).vertices().count(), 4);
+        assert_eq!(mesh.face(new_face).vertices().count(), 4);
+        let kept: Vec<_> = mesh
+            .face(face)
+            .vertices()
+            .map(|v| v.point().position())
+            .collect();
+        for position in positions[0..4].iter() {
+            assert!(kept.contains(position));
+        }
+
+        assert!(mesh.split_face(face, corners[0], corners[1]).is_none());
+        assert!(mesh.split_face(face, corners[1], corners[1]).is_none());
+        assert!(mesh.split_face(new_face, corners[1], corners[4]).is_none());
+        assert_eq!(mesh.face_count(), 2);
+    }
+
+    #[test]
+    fn split_face_accepts_vertices_of_neighbors() {
+        let _ = env_logger::try_init();
+        let mut mesh = Mesh::grid(1.0, 1.0, 2, 1);
+        let faces: Vec<_> = mesh.faces().map(|face| face.handle).collect();
+        // The corner of the second face at a point shared with the first.
+        let shared = mesh
+            .face(faces[1])
+            .vertices()
+            .find(|v| {
+                mesh.face(faces[0])
+                    .vertices()
+                    .any(|w| w.point().handle == v.point().handle)
+            })
+            .unwrap();
+        let opposite = mesh
+            .face(faces[0])
+            .vertices()
+            .find(|v| {
+                let (a, b) = (v.point().position(), shared.point().position());
+                a.x != b.x && a.z != b.z
+            })
+            .unwrap();
+        let (shared, opposite) = (shared.handle, opposite.handle);
+        let new_face = mesh.split_face(faces[0], shared, opposite).unwrap();
+        assert!(mesh.is_valid());
+        assert_eq!(mesh.face_count(), 3);
+        assert_eq!(mesh.face(faces[0]).vertices().count(), 3);
+        assert_eq!(mesh.face(new_face).vertices().count(), 3);
+    }
+
+    #[test]
+    fn can_join_faces() {
+        let _ = env_logger::try_init();
+        let mut mesh = Mesh::grid(1.0, 1.0, 2, 1);
+        let edge = mesh
+            .edges()
+            .find(|edge| !edge.is_boundary())
+            .unwrap()
+            .handle;
+        let face = mesh.edge(edge).face().handle;
+        assert_eq!(mesh.join_faces(edge), Some(face));
+        assert!(mesh.is_valid());
+        assert_eq!(mesh.face_count(), 1);
+        assert_eq!(mesh.face(face).vertices().count(), 6);
+        assert_eq!(mesh.edge_count(), 12);
+        assert_eq!(mesh.vertex_count(), 12);
+
+        let boundary = mesh.face(face).root_edge().adjacent().handle;
+        assert!(mesh.join_faces(boundary).is_none());
+    }
+
+    #[test]
+    fn joining_is_undone_by_splitting() {
+        let _ = env_logger::try_init();
+        let mut mesh = Mesh::icosphere(1.0, 1);
+        let (face_count, edge_count) = (mesh.face_count(), mesh.edge_count());
+        let edge = mesh.edges().next().unwrap().handle;
+        let face = mesh.join_faces(edge).unwrap();
+        assert!(mesh.is_valid());
+        assert_eq!(mesh.face_count(), face_count - 1);
+        assert_eq!(mesh.face(face).vertices().count(), 4);
+
+        let corners: Vec<_> = mesh.face(face).vertices().map(|v| v.handle).collect();
+        assert!(mesh.split_face(face, corners[0], corners[2]).is_some());
+        assert!(mesh.is_valid());
+        assert_eq!(mesh.face_count(), face_count);
+        assert_eq!(mesh.edge_count(), edge_count);
+    }
+
+    #[test]
+    fn rejects_joining_faces_sharing_several_edges() {
+        let _ = env_logger::try_init();
+        let mut mesh = Mesh::unit_cube();
+        let on_side = |face: FaceProxy, side: fn(&Position) -> bool| {
+            face.vertices().all(|v| side(&v.point().position()))
+        };
+        let edge = mesh
+            .edges()
+            .find(|e| {
+                on_side(e.face(), |p| p.y == 1.0) && on_side(e.adjacent().face(), |p| p.z == 0.0)
+            })
+            .unwrap()
+            .handle;
+        let face = mesh.join_faces(edge).unwrap();
+        assert!(mesh.is_valid());
+
+        // The right side touches both the top and the front.
+        let edge = mesh
+            .face(face)
+            .edges()
+            .find(|e| on_side(e.adjacent().face(), |p| p.x == 1.0))
+            .unwrap()
+            .handle;
+        assert!(mesh.join_faces(edge).is_none());
+        assert_eq!(mesh.face_count(), 5);
+    }
+
+    fn vertex_at(mesh: &Mesh, position: Position) -> VertexHandle {
+        mesh.vertices()
+            .find(|v| v.point().position() == position)
+            .unwrap()
+            .handle
+    }
+
+    #[test]
+    fn can_dissolve_vertex() {
+        let _ = env_logger::try_init();
+        let mut mesh = Mesh::grid(1.0, 1.0, 2, 2);
+        let center = vertex_at(&mesh, Position::origin());
+        let face = mesh.remove_vertex(center, VertexRemoval::Dissolve).unwrap();
+        assert!(mesh.is_valid());
+        assert_eq!(mesh.face_count(), 1);
+        assert_eq!(mesh.point_count(), 8);
+        assert_eq!(mesh.face(face).vertices().count(), 8);
+        assert_eq!(mesh.full_edge_count(), 8);
+
+        let mut mesh = Mesh::icosphere(1.0, 0);
+        let vertex = mesh.vertices().next().unwrap().handle;
+        let face = mesh.remove_vertex(vertex, VertexRemoval::Dissolve).unwrap();
+        assert!(mesh.is_valid());
+        assert_eq!(mesh.face_count(), 16);
+        assert_eq!(mesh.point_count(), 11);
+        assert_eq!(mesh.face(face).vertices().count(), 5);
+        assert!(mesh.full_edges().all(|e| !e.is_boundary()));
+    }
+
+    #[test]
+    fn can_dissolve_boundary_vertex() {
+        let _ = env_logger::try_init();
+        let mut mesh = Mesh::grid(1.0, 1.0, 2, 2);
+        let vertex = vertex_at(&mesh, Position::new(0.5, 0.0, 0.0));
+        let face = mesh.remove_vertex(vertex, VertexRemoval::Dissolve).unwrap();
+        assert!(mesh.is_valid());
+        assert_eq!(mesh.face_count(), 3);
+        assert_eq!(mesh.point_count(), 8);
+        assert_eq!(mesh.face(face).vertices().count(), 5);
+        assert_eq!(mesh.full_edges().filter(|e| e.is_boundary()).count(), 7);
+    }
+
+    #[test]
+    fn can_delete_vertex() {
+        let _ = env_logger::try_init();
+        let mut mesh = Mesh::icosphere(1.0, 0);
+        let vertex = mesh.vertices().next().unwrap().handle;
+        assert!(mesh.remove_vertex(vertex, VertexRemoval::Delete).is_none());
+        assert!(mesh.is_valid());
+        assert_eq!(mesh.face_count(), 15);
+        assert_eq!(mesh.point_count(), 11);
+        assert_eq!(mesh.full_edge_count(), 25);
+        assert_eq!(mesh.full_edges().filter(|e| e.is_boundary()).count(), 5);
+
+        let mut mesh = Mesh::grid(1.0, 1.0, 2, 2);
+        let corner = vertex_at(&mesh, Position::new(-0.5, 0.0, -0.5));
+        mesh.remove_vertex(corner, VertexRemoval::Delete);
+        assert!(mesh.is_valid());
+        assert_eq!(mesh.face_count(), 3);
+        assert_eq!(mesh.point_count(), 8);
+    }
+
+    #[test]
+    fn can_delete_faces() {
+        let _ = env_logger::try_init();
+        let mut mesh = Mesh::unit_cube();
+        let faces: Vec<_> = mesh.faces().take(2).map(|f| f.handle).collect();
+        mesh.delete_faces(&faces, true);
+        assert!(mesh.is_valid());
+        assert_eq!(mesh.face_count(), 4);
+        assert!(mesh
+            .edges()
+            .all(|e| e.face().is_valid() || e.adjacent().face().is_valid()));
+
+        let mut mesh = Mesh::grid(1.0, 1.0, 2, 2);
+        let faces: Vec<_> = mesh.faces().map(|f| f.handle).collect();
+        mesh.delete_faces(&faces[..1], false);
+        assert!(mesh.is_valid());
+        assert_eq!(mesh.face_count(), 3);
+        assert_eq!(mesh.point_count(), 9);
+        assert_eq!(mesh.full_edge_count(), 10);
+
+        let mut mesh = Mesh::grid(1.0, 1.0, 2, 2);
+        let faces: Vec<_> = mesh.faces().map(|f| f.handle).collect();
+        mesh.delete_faces(&faces, true);
+        assert!(mesh.is_valid());
+        assert_eq!(mesh.face_count(), 0);
+        assert_eq!(mesh.edge_count(), 0);
+        assert_eq!(mesh.vertex_count(), 0);
+        assert_eq!(mesh.point_count(), 0);
+    }
+
+    #[test]
+    fn can_delete_edges() {
+        let _ = env_logger::try_init();
+        let mut mesh = Mesh::grid(1.0, 1.0, 2, 2);
+        let center = vertex_at(&mesh, Position::origin());
+        let edge = mesh.vertex(center).edge().handle;
+        mesh.delete_edges(&[edge], true);
+        assert!(mesh.is_valid());
+        assert_eq!(mesh.face_count(), 2);
+        assert_eq!(mesh.point_count(), 6);
+        assert_eq!(mesh.full_edge_count(), 7);
+
+        let mut mesh = Mesh::default();
+        let p0 = mesh.add(Point::from_position(0.0, 0.0, 0.0));
+        let p1 = mesh.add(Point::from_position(1.0, 0.0, 0.0));
+        let (edge, _) = mesh.make_edge((p0, p1));
+        mesh.delete_edges(&[edge], false);
+        assert_eq!(mesh.edge_count(), 0);
+        assert_eq!(mesh.vertex_count(), 0);
+        assert_eq!(mesh.point_count(), 2);
+    }
+
+    #[test]
+    fn can_poke_face() {
+        let _ = env_logger::try_init();
+        let mut mesh = Mesh::unit_cube();
+        let face = mesh.faces().next().unwrap().handle;
+        let center: Normal = mesh
+            .face(face)
+            .vertices()
+            .map(|v| v.point().position().coords)
+            .sum::<Normal>()
+            / 4.0;
+        let triangles = mesh.poke_face(face);
+        assert!(mesh.is_valid());
+        assert_eq!(triangles.len(), 4);
+        assert_eq!(triangles[0], face);
+        assert_eq!(mesh.face_count(), 9);
+        assert_eq!(mesh.point_count(), 9);
+        assert_eq!(mesh.full_edge_count(), 16);
+        for triangle in triangles {
+            let triangle = mesh.face(triangle);
+            assert_eq!(triangle.vertices().count(), 3);
+            assert!(triangle
+                .vertices()
+                .any(|v| v.point().position() == Position::from(center)));
+        }
+        assert!(mesh.edges().all(|e| e.face().is_valid()));
+    }
+
+    #[test]
+    fn can_poke_face_at_position() {
+        let _ = env_logger::try_init();
+        let mut mesh = Mesh::grid(1.0, 1.0, 1, 1);
+        let face = mesh.faces().next().unwrap().handle;
+        let spike = Position::new(0.0, 1.0, 0.0);
+        let triangles = mesh.poke_face_at(face, spike);
+        assert!(mesh.is_valid());
+        assert_eq!(triangles.len(), 4);
+        assert_eq!(mesh.full_edges().filter(|e| e.is_boundary()).count(), 4);
+        assert!(mesh.vertices().any(|v| v.point().position() == spike));
+    }
+
+    #[test]
+    fn can_poke_all_faces() {
+        let _ = env_logger::try_init();
+        let mut mesh = Mesh::unit_cube();
+        assert_eq!(mesh.poke_faces(), 18);
+        assert!(mesh.is_valid());
+        assert_eq!(mesh.face_count(), 24);
+        assert_eq!(mesh.point_count(), 14);
+        assert_eq!(mesh.full_edge_count(), 36);
+        assert!((mesh.volume() - 1.0).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,592 @@
+//! Construction of meshes from indexed polygons and parametric primitives.
+
+use std::collections::{HashMap, HashSet};
+use std::f32::consts::PI;
+
+use crate::data::Position;
+use crate::elements::{Face, Point};
+use crate::handles::{FaceHandle, HalfEdgeHandle, PointHandle};
+use crate::mesh::Mesh;
+use crate::operators::Fan;
+use crate::traits::*;
+
+impl Mesh {
+    /// Builds a connected mesh from a list of positions and polygons
+    /// indexing into it.
+    ///
+    /// Polygons are expected to be wound consistently. See `Mesh::add_polygons`.
+    pub fn from_polygons<P: AsRef<[usize]>>(positions: &[Position], polygons: &[P]) -> Mesh {
+        let mut mesh = Mesh::default();
+        let points: Vec<PointHandle> = positions
+            .iter()
+            .map(|position| mesh.add(Point::new(*position)))
+            .collect();
+        let _ = mesh.add_polygons(&points, polygons);
+        mesh
+    }
+
+    /// Adds faces for polygons indexing into `points`, sharing edges
+    /// between neighboring polygons.
+    ///
+    /// Returns the face handles in the same order as the polygons. The root
+    /// edge of each face starts at the first point of its polygon.
+    ///
+    /// An edge is only shared when its neighbor runs in the opposite
+    /// direction. Edges used more than twice or by polygons with mismatched
+    /// winding are left on the boundary.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a polygon has less than 3 corners.
+    pub fn add_polygons<P: AsRef<[usize]>>(
+        &mut self,
+        points: &[PointHandle],
+        polygons: &[P],
+    ) -> Vec<FaceHandle> {
+        let mut half_edges: HashMap<(PointHandle, PointHandle), HalfEdgeHandle> = HashMap::new();
+        let mut faces = Vec::with_capacity(polygons.len());
+
+        for polygon in polygons {
+            let polygon = polygon.as_ref();
+            assert!(polygon.len() >= 3);
+            let face = self.add(Face::default());
+
+            let mut loop_edges = Vec::with_capacity(polygon.len());
+            for (offset, index) in polygon.iter().enumerate() {
+                let p0 = points[*index];
+                let p1 = points[polygon[(offset + 1) % polygon.len()]];
+                let unused_twin = half_edges
+                    .get(&(p0, p1))
+                    .cloned()
+                    .filter(|edge| !self.edge(*edge).face().is_valid());
+                let edge = match unused_twin {
+                    Some(edge) => edge,
+                    None => {
+                        let (e0, e1) = self.make_edge((p0, p1));
+                        half_edges.entry((p0, p1)).or_insert(e0);
+                        half_edges.entry((p1, p0)).or_insert(e1);
+                        e0
+                    }
+                };
+                if let Some(element) = self.get(edge) {
+                    element.data_mut().face = face;
+                }
+                loop_edges.push(edge);
+            }
+
+            for (offset, edge) in loop_edges.iter().enumerate() {
+                let next = loop_edges[(offset + 1) % loop_edges.len()];
+                self.edge(*edge).connect_to(&self.edge(next));
+            }
+            if let Some(face) = self.get(face) {
+                face.data_mut().root_edge = loop_edges[0];
+            }
+            faces.push(face);
+        }
+
+        faces
+    }
+
+    /// Builds the dual of the mesh.
+    ///
+    /// Every face becomes a point at its centroid, and every point
+    /// surrounded by faces becomes a face connecting the points of those
+    /// faces in order. Points on the boundary don't get a face.
+    pub fn dual(&self) -> Mesh {
+        let mut indices = HashMap::new();
+        let mut positions = Vec::with_capacity(self.face_count());
+        for face in self.faces() {
+            indices.insert(face.handle.index(), positions.len());
+            positions.push(face.centroid());
+        }
+
+        let mut visited = HashSet::new();
+        let mut polygons = Vec::new();
+        let vertices: Vec<_> = self
+            .vertices()
+            .map(|vert| (vert.point().handle.index(), vert.edge().handle))
+            .collect();
+        for (point, edge) in vertices {
+            if !visited.insert(point) {
+                continue;
+            }
+            let fan = Fan::new(self, edge);
+            if !fan.closed {
+                continue;
+            }
+            let polygon: Vec<usize> = fan
+                .edges
+                .iter()
+                .filter_map(|edge| indices.get(&self.edge(*edge).face().handle.index()))
+                .cloned()
+                .collect();
+            if polygon.len() >= 3 {
+                polygons.push(polygon);
+            }
+        }
+        Mesh::from_polygons(&positions, &polygons)
+    }
+
+    /// A flat grid in the XZ plane centered at the origin and facing +Y.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `columns` or `rows` is zero.
+    pub fn grid(width: f32, depth: f32, columns: usize, rows: usize) -> Self {
+        assert!(columns >= 1 && rows >= 1);
+        let mut positions = Vec::with_capacity((columns + 1) * (rows + 1));
+        for column in 0..=columns {
+            for row in 0..=rows {
+                positions.push(Position::new(
+                    width * (column as f32 / columns as f32 - 0.5),
+                    0.0,
+                    depth * (row as f32 / rows as f32 - 0.5),
+                ));
+            }
+        }
+
+        let index = |column: usize, row: usize| column * (rows + 1) + row;
+        let mut polygons = Vec::with_capacity(columns * rows);
+        for column in 0..columns {
+            for row in 0..rows {
+                polygons.push([
+                    index(column, row),
+                    index(column, row + 1),
+                    index(column + 1, row + 1),
+                    index(column + 1, row),
+                ]);
+            }
+        }
+
+        Mesh::from_polygons(&positions, &polygons)
+    }
+
+    /// A sphere made of `segments` slices around the Y axis and `rings`
+    /// stacks from pole to pole.
+    ///
+    /// The stacks at the poles are made of triangles, all others of quads.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `segments` is less than 3 or `rings` less than 2.
+    pub fn uv_sphere(radius: f32, segments: usize, rings: usize) -> Self {
+        assert!(segments >= 3 && rings >= 2);
+        let mut positions = vec![Position::new(0.0, radius, 0.0)];
+        for ring in 1..rings {
+            let theta = PI * ring as f32 / rings as f32;
+            for segment in 0..segments {
+                let phi = 2.0 * PI * segment as f32 / segments as f32;
+                positions.push(Position::new(
+                    radius * theta.sin() * phi.cos(),
+                    radius * theta.cos(),
+                    radius * theta.sin() * phi.sin(),
+                ));
+            }
+        }
+        positions.push(Position::new(0.0, -radius, 0.0));
+        let bottom = positions.len() - 1;
+
+        let index = |ring: usize, segment: usize| 1 + (ring - 1) * segments + segment % segments;
+        let mut polygons: Vec<Vec<usize>> = Vec::new();
+        for segment in 0..segments {
+            polygons.push(vec![0, index(1, segment + 1), index(1, segment)]);
+        }
+        for ring in 1..rings - 1 {
+            for segment in 0..segments {
+                polygons.push(vec![
+                    index(ring, segment),
+                    index(ring, segment + 1),
+                    index(ring + 1, segment + 1),
+                    index(ring + 1, segment),
+                ]);
+            }
+        }
+        for segment in 0..segments {
+            polygons.push(vec![
+                bottom,
+                index(rings - 1, segment),
+                index(rings - 1, segment + 1),
+            ]);
+        }
+
+        Mesh::from_polygons(&positions, &polygons)
+    }
+
+    /// A sphere built by repeatedly splitting the triangles of an icosahedron.
+    ///
+    /// Every subdivision level quadruples the number of faces.
+    pub fn icosphere(radius: f32, subdivisions: usize) -> Self {
+        let t = (1.0 + 5.0f32.sqrt()) / 2.0;
+        let mut positions: Vec<Position> = [
+            (-1.0, t, 0.0),
+            (1.0, t, 0.0),
+            (-1.0, -t, 0.0),
+            (1.0, -t, 0.0),
+            (0.0, -1.0, t),
+            (0.0, 1.0, t),
+            (0.0, -1.0, -t),
+            (0.0, 1.0, -t),
+            (t, 0.0, -1.0),
+            (t, 0.0, 1.0),
+            (-t, 0.0, -1.0),
+            (-t, 0.0, 1.0),
+        ]
+        .iter()
+        .map(|(x, y, z)| Position::from(Position::new(*x, *y, *z).coords.normalize() * radius))
+        .collect();
+        let mut triangles: Vec<[usize; 3]> = vec![
+            [0, 11, 5],
+            [0, 5, 1],
+            [0, 1, 7],
+            [0, 7, 10],
+            [0, 10, 11],
+            [1, 5, 9],
+            [5, 11, 4],
+            [11, 10, 2],
+            [10, 7, 6],
+            [7, 1, 8],
+            [3, 9, 4],
+            [3, 4, 2],
+            [3, 2, 6],
+            [3, 6, 8],
+            [3, 8, 9],
+            [4, 9, 5],
+            [2, 4, 11],
+            [6, 2, 10],
+            [8, 6, 7],
+            [9, 8, 1],
+        ];
+
+        for _ in 0..subdivisions {
+            let mut midpoints: HashMap<(usize, usize), usize> = HashMap::new();
+            let mut midpoint = |a: usize, b: usize, positions: &mut Vec<Position>| {
+                let key = (a.min(b), a.max(b));
+                *midpoints.entry(key).or_insert_with(|| {
+                    let coords = (positions[a].coords + positions[b].coords) * 0.5;
+                    positions.push(Position::from(coords.normalize() * radius));
+                    positions.len() - 1
+                })
+            };
+            let mut refined = Vec::with_capacity(triangles.len() * 4);
+            for [a, b, c] in triangles {
+                let ab = midpoint(a, b, &mut positions);
+                let bc = midpoint(b, c, &mut positions);
+                let ca = midpoint(c, a, &mut positions);
+                refined.push([a, ab, ca]);
+                refined.push([b, bc, ab]);
+                refined.push([c, ca, bc]);
+                refined.push([ab, bc, ca]);
+            }
+            triangles = refined;
+        }
+
+        Mesh::from_polygons(&positions, &triangles)
+    }
+
+    /// A cylinder around the Y axis centered at the origin.
+    ///
+    /// When `capped` the ends are closed with a single polygon each,
+    /// otherwise they are left open.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `segments` is less than 3.
+    pub fn cylinder(radius: f32, height: f32, segments: usize, capped: bool) -> Self {
+        assert!(segments >= 3);
+        let mut positions = Vec::with_capacity(segments * 2);
+        for y in [-0.5 * height, 0.5 * height].iter() {
+            for segment in 0..segments {
+                let phi = 2.0 * PI * segment as f32 / segments as f32;
+                positions.push(Position::new(radius * phi.cos(), *y, radius * phi.sin()));
+            }
+        }
+
+        let bottom = |segment: usize| segment % segments;
+        let top = |segment: usize| segments + segment % segments;
+        let mut polygons: Vec<Vec<usize>> = (0..segments)
+            .map(|segment| {
+                vec![
+                    bottom(segment),
+                    top(segment),
+                    top(segment + 1),
+                    bottom(segment + 1),
+                ]
+            })
+            .collect();
+        if capped {
+            polygons.push((0..segments).map(bottom).collect());
+            polygons.push((0..segments).rev().map(top).collect());
+        }
+
+        Mesh::from_polygons(&positions, &polygons)
+    }
+
+    /// A cone around the Y axis with its apex pointing up, centered at
+    /// the origin.
+    ///
+    /// When `capped` the base is closed with a single polygon.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `segments` is less than 3.
+    pub fn cone(radius: f32, height: f32, segments: usize, capped: bool) -> Self {
+        assert!(segments >= 3);
+        let mut positions = vec![Position::new(0.0, 0.5 * height, 0.0)];
+        for segment in 0..segments {
+            let phi = 2.0 * PI * segment as f32 / segments as f32;
+            positions.push(Position::new(
+                radius * phi.cos(),
+                -0.5 * height,
+                radius * phi.sin(),
+            ));
+        }
+
+        let base = |segment: usize| 1 + segment % segments;
+        let mut polygons: Vec<Vec<usize>> = (0..segments)
+            .map(|segment| vec![0, base(segment + 1), base(segment)])
+            .collect();
+        if capped {
+            polygons.push((0..segments).map(base).collect());
+        }
+
+        Mesh::from_polygons(&positions, &polygons)
+    }
+
+    /// A torus around the Y axis centered at the origin.
+    ///
+    /// `major_radius` is the distance from the center to the middle of
+    /// the tube and `minor_radius` the radius of the tube.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `major_segments` or `minor_segments` is less than 3.
+    pub fn torus(
+        major_radius: f32,
+        minor_radius: f32,
+        major_segments: usize,
+        minor_segments: usize,
+    ) -> Self {
+        assert!(major_segments >= 3 && minor_segments >= 3);
+        let mut positions = Vec::with_capacity(major_segments * minor_segments);
+        for major in 0..major_segments {
+            let u = 2.0 * PI * major as f32 / major_segments as f32;
+            for minor in 0..minor_segments {
+                let v = 2.0 * PI * minor as f32 / minor_segments as f32;
+                let distance = major_radius + minor_radius * v.cos();
+                positions.push(Position::new(
+                    distance * u.cos(),
+                    minor_radius * v.sin(),
+                    distance * u.sin(),
+                ));
+            }
+        }
+
+        let index = |major: usize, minor: usize| {
+            (major % major_segments) * minor_segments + minor % minor_segments
+        };
+        let mut polygons = Vec::with_capacity(major_segments * minor_segments);
+        for major in 0..major_segments {
+            for minor in 0..minor_segments {
+                polygons.push([
+                    index(major, minor),
+                    index(major, minor + 1),
+                    index(major + 1, minor + 1),
+                    index(major + 1, minor),
+                ]);
+            }
+        }
+
+        Mesh::from_polygons(&positions, &polygons)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::*;
+    use crate::*;
+    use nalgebra as na;
+
+    /// Asserts that every face normal points away from `center_of` the face.
+    fn assert_faces_point_outward<F>(mesh: &Mesh, center_of: F)
+    where
+        F: Fn(&Position) -> Position,
+    {
+        for face in mesh.faces() {
+            let positions: Vec<Position> = face.vertices().map(|v| v.point().position()).collect();
+            let mut normal: Normal = na::zero();
+            let mut centroid = na::Vector3::zeros();
+            for (index, current) in positions.iter().enumerate() {
+                let next = positions[(index + 1) % positions.len()];
+                normal += current.coords.cross(&next.coords);
+                centroid += current.coords;
+            }
+            let centroid = Position::from(centroid / positions.len() as f32);
+            let outward = centroid - center_of(&centroid);
+            assert!(
+                normal.dot(&outward) > 0.0,
+                "Face {} points inward",
+                face.handle.index()
+            );
+        }
+    }
+
+    #[test]
+    fn can_build_mesh_from_polygons() {
+        let _ = env_logger::try_init();
+        let positions = [
+            Position::new(0.0, 0.0, 0.0),
+            Position::new(1.0, 0.0, 0.0),
+            Position::new(1.0, 1.0, 0.0),
+            Position::new(0.0, 1.0, 0.0),
+        ];
+        let mesh = Mesh::from_polygons(&positions, &[[0, 1, 2], [0, 2, 3]]);
+        assert!(mesh.is_valid());
+        assert_eq!(mesh.point_count(), 4);
+        assert_eq!(mesh.face_count(), 2);
+        assert_eq!(mesh.full_edge_count(), 5);
+        assert_eq!(boundary_edge_count(&mesh), 4);
+    }
+
+    #[test]
+    fn unit_cube_is_valid() {
+        let _ = env_logger::try_init();
+        let mesh = Mesh::unit_cube();
+        assert!(mesh.is_valid());
+        assert_eq!(mesh.face_count(), 6);
+        assert_eq!(mesh.full_edge_count(), 12);
+        assert_eq!(boundary_edge_count(&mesh), 0);
+        assert_faces_point_outward(&mesh, |_| Position::new(0.5, 0.5, 0.5));
+    }
+
+    #[test]
+    fn can_build_grid() {
+        let _ = env_logger::try_init();
+        let mesh = Mesh::grid(2.0, 1.0, 4, 2);
+        assert!(mesh.is_valid());
+        assert_eq!(mesh.point_count(), 15);
+        assert_eq!(mesh.face_count(), 8);
+        assert_eq!(boundary_edge_count(&mesh), 12);
+        assert_eq!(euler_characteristic(&mesh), 1);
+        assert_faces_point_outward(&mesh, |p| Position::new(p.x, -1.0, p.z));
+    }
+
+    #[test]
+    fn can_build_uv_sphere() {
+        let _ = env_logger::try_init();
+        let mesh = Mesh::uv_sphere(1.0, 8, 6);
+        assert!(mesh.is_valid());
+        assert_eq!(mesh.point_count(), 8 * 5 + 2);
+        assert_eq!(mesh.face_count(), 8 * 6);
+        assert_eq!(boundary_edge_count(&mesh), 0);
+        assert_eq!(euler_characteristic(&mesh), 2);
+        assert_faces_point_outward(&mesh, |_| Position::origin());
+    }
+
+    #[test]
+    fn can_build_icosphere() {
+        let _ = env_logger::try_init();
+        let mesh = Mesh::icosphere(2.0, 0);
+        assert!(mesh.is_valid());
+        assert_eq!(mesh.point_count(), 12);
+        assert_eq!(mesh.face_count(), 20);
+
+        let mesh = Mesh::icosphere(2.0, 2);
+        assert!(mesh.is_valid());
+        assert_eq!(mesh.point_count(), 162);
+        assert_eq!(mesh.face_count(), 320);
+        assert_eq!(boundary_edge_count(&mesh), 0);
+        assert_eq!(euler_characteristic(&mesh), 2);
+        assert_faces_point_outward(&mesh, |_| Position::origin());
+        for point in mesh.vertices().map(|v| v.point().position()) {
+            assert!((point.coords.norm() - 2.0).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn can_build_cylinder() {
+        let _ = env_logger::try_init();
+        let mesh = Mesh::cylinder(1.0, 2.0, 6, true);
+        assert!(mesh.is_valid());
+        assert_eq!(mesh.point_count(), 12);
+        assert_eq!(mesh.face_count(), 8);
+        assert_eq!(boundary_edge_count(&mesh), 0);
+        assert_eq!(euler_characteristic(&mesh), 2);
+        assert_faces_point_outward(&mesh, |_| Position::origin());
+
+        let mesh = Mesh::cylinder(1.0, 2.0, 6, false);
+        assert!(mesh.is_valid());
+        assert_eq!(mesh.face_count(), 6);
+        assert_eq!(boundary_edge_count(&mesh), 12);
+        assert_eq!(euler_characteristic(&mesh), 0);
+    }
+
+    #[test]
+    fn can_build_cone() {
+        let _ = env_logger::try_init();
+        let mesh = Mesh::cone(1.0, 2.0, 8, true);
+        assert!(mesh.is_valid());
+        assert_eq!(mesh.point_count(), 9);
+        assert_eq!(mesh.face_count(), 9);
+        assert_eq!(boundary_edge_count(&mesh), 0);
+        assert_eq!(euler_characteristic(&mesh), 2);
+        assert_faces_point_outward(&mesh, |_| Position::origin());
+
+        let mesh = Mesh::cone(1.0, 2.0, 8, false);
+        assert!(mesh.is_valid());
+        assert_eq!(boundary_edge_count(&mesh), 8);
+    }
+
+    #[test]
+    fn can_build_torus() {
+        let _ = env_logger::try_init();
+        let mesh = Mesh::torus(2.0, 0.5, 12, 6);
+        assert!(mesh.is_valid());
+        assert_eq!(mesh.point_count(), 72);
+        assert_eq!(mesh.face_count(), 72);
+        assert_eq!(boundary_edge_count(&mesh), 0);
+        assert_eq!(euler_characteristic(&mesh), 0);
+        assert_faces_point_outward(&mesh, |p| {
+            let radial = na::Vector3::new(p.x, 0.0, p.z).normalize() * 2.0;
+            Position::from(radial)
+        });
+    }
+
+    #[test]
+    fn dual_of_cube_is_octahedron() {
+        let _ = env_logger::try_init();
+        let dual = Mesh::unit_cube().dual();
+        assert!(dual.is_valid());
+        assert_eq!(dual.point_count(), 6);
+        assert_eq!(dual.face_count(), 8);
+        assert_eq!(dual.full_edge_count(), 12);
+        assert_eq!(boundary_edge_count(&dual), 0);
+        assert!(dual.faces().all(|face| face.vertices().count() == 3));
+    }
+
+    #[test]
+    fn dual_of_icosphere_is_goldberg_polyhedron() {
+        let _ = env_logger::try_init();
+        let sphere = Mesh::icosphere(1.0, 1);
+        let dual = sphere.dual();
+        assert!(dual.is_valid());
+        assert_eq!(dual.point_count(), sphere.face_count());
+        assert_eq!(dual.face_count(), sphere.point_count());
+        assert_eq!(dual.full_edge_count(), sphere.full_edge_count());
+        assert_eq!(euler_characteristic(&dual), 2);
+        let pentagons = dual.faces().filter(|f| f.vertices().count() == 5).count();
+        let hexagons = dual.faces().filter(|f| f.vertices().count() == 6).count();
+        assert_eq!((pentagons, hexagons), (12, 30));
+        assert_faces_point_outward(&dual, |_| Position::origin());
+    }
+
+    #[test]
+    fn dual_skips_boundary_points() {
+        let _ = env_logger::try_init();
+        let dual = Mesh::grid(3.0, 3.0, 3, 3).dual();
+        assert!(dual.is_valid());
+        assert_eq!(dual.point_count(), 9);
+        assert_eq!(dual.face_count(), 4);
+        assert!(dual.faces().all(|face| face.vertices().count() == 4));
+        assert_faces_point_outward(&dual, |p| Position::new(p.x, -1.0, p.z));
+    }
+}
@@ -1,10 +1,14 @@
 //! Facades over a mesh and element handle to enable easy topology traversals.
 
+use crate::data::{Normal, Position};
 use crate::elements::{Face, HalfEdge, Point, Vertex};
-use crate::handles::{FaceHandle, HalfEdgeHandle, PointHandle, VertexHandle};
-use crate::iterators::{FaceEdges, FaceVertices, FaceTriangles, VertexCirculator};
+use crate::handles::{FaceHandle, FullEdgeHandle, HalfEdgeHandle, PointHandle, VertexHandle};
+use crate::iterators::{
+    FaceEdges, FaceTriangles, FaceVertices, VertexCirculator, VertexFaceCirculator,
+};
 use crate::mesh::Mesh;
 use crate::traits::*;
+use nalgebra as na;
 use std::cell::{Ref, RefMut};
 
 pub trait ElementProxy<'mesh, E: Element + 'mesh> {
@@ -23,16 +27,27 @@ pub trait ElementProxy<'mesh, E: Element + 'mesh> {
     }
 
     #[inline]
-    fn data(&'mesh self) -> Option<Ref<E::Data>> {
+    fn data(&'mesh self) -> Option<Ref<'mesh, E::Data>> {
         self.element().map(|e| e.data())
     }
 
     #[inline]
-    fn data_mut(&'mesh self) -> Option<RefMut<E::Data>> {
+    fn data_mut(&'mesh self) -> Option<RefMut<'mesh, E::Data>> {
         self.element().map(|e| e.data_mut())
     }
 }
 
+/// Cotangent of the angle at `corner` in the triangle spanned with `a` and `b`.
+fn cotangent(corner: &Position, a: &Position, b: &Position) -> f32 {
+    let (u, v) = (a - corner, b - corner);
+    let sine = u.cross(&v).norm();
+    if sine <= f32::EPSILON {
+        0.0
+    } else {
+        u.dot(&v) / sine
+    }
+}
+
 /// Function set for operations related to the Face struct
 #[derive(Debug, Copy, Clone)]
 pub struct FaceProxy<'mesh> {
@@ -69,6 +84,113 @@ impl<'mesh> FaceProxy<'mesh> {
     }
 }
 
+impl<'mesh> FaceProxy<'mesh> {
+    fn positions(&self) -> Vec<Position> {
+        self.vertices()
+            .map(|vert| vert.point().position())
+            .collect()
+    }
+
+    /// Sum of the cross products of the face loop (Newell's method).
+    ///
+    /// The length of the result is twice the area of the face.
+    pub fn area_vector(&self) -> Normal {
+        let positions = self.positions();
+        let mut area_vector: Normal = na::zero();
+        for (index, current) in positions.iter().enumerate() {
+            let next = positions[(index + 1) % positions.len()];
+            area_vector += current.coords.cross(&next.coords);
+        }
+        area_vector
+    }
+
+    pub fn area(&self) -> f32 {
+        self.area_vector().norm() * 0.5
+    }
+
+    /// Unit normal following the winding of the face, zero for degenerate
+    /// faces.
+    pub fn normal(&self) -> Normal {
+        self.area_vector()
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_else(na::zero)
+    }
+
+    /// Center of mass of the face area, or the average of the corners for
+    /// degenerate faces.
+    pub fn centroid(&self) -> Position {
+        let positions = self.positions();
+        if positions.is_empty() {
+            return Position::origin();
+        }
+        let normal = self.normal();
+        let origin = positions[0];
+        let mut weighted: Normal = na::zero();
+        let mut total = 0.0;
+        for index in 1..positions.len().saturating_sub(1) {
+            let (b, c) = (positions[index], positions[index + 1]);
+            let area = (b - origin).cross(&(c - origin)).dot(&normal);
+            weighted += (origin.coords + b.coords + c.coords) * (area / 3.0);
+            total += area;
+        }
+        if total.abs() > f32::EPSILON {
+            Position::from(weighted / total)
+        } else {
+            let sum: Normal = positions.iter().map(|p| p.coords).sum();
+            Position::from(sum / positions.len() as f32)
+        }
+    }
+
+    pub fn perimeter(&self) -> f32 {
+        let positions = self.positions();
+        (0..positions.len())
+            .map(|index| (positions[(index + 1) % positions.len()] - positions[index]).norm())
+            .sum()
+    }
+
+    /// Whether all corners lie within `tolerance` of the plane through the
+    /// centroid.
+    pub fn is_planar(&self, tolerance: f32) -> bool {
+        let (normal, centroid) = (self.normal(), self.centroid());
+        self.positions()
+            .iter()
+            .all(|p| (p - centroid).dot(&normal).abs() <= tolerance)
+    }
+
+    /// Whether no corner bends against the winding of the face.
+    pub fn is_convex(&self) -> bool {
+        let positions = self.positions();
+        let normal = self.normal();
+        let count = positions.len();
+        (0..count).all(|index| {
+            let prev = positions[(index + count - 1) % count];
+            let next = positions[(index + 1) % count];
+            let current = positions[index];
+            (current - prev).cross(&(next - current)).dot(&normal) >= -f32::EPSILON
+        })
+    }
+
+    /// Ratio of the circumradius to twice the inradius of a triangle, which
+    /// is one for equilateral triangles and grows as they degenerate.
+    ///
+    /// Returns `None` for faces which aren't triangles.
+    pub fn aspect_ratio(&self) -> Option<f32> {
+        let positions = self.positions();
+        if positions.len() != 3 {
+            return None;
+        }
+        let a = (positions[1] - positions[0]).norm();
+        let b = (positions[2] - positions[1]).norm();
+        let c = (positions[0] - positions[2]).norm();
+        let s = (a + b + c) * 0.5;
+        let product = (s - a) * (s - b) * (s - c);
+        if product <= 0.0 {
+            return Some(f32::INFINITY);
+        }
+        Some(a * b * c / (8.0 * product))
+    }
+}
+
 impl<'mesh> IsValid for FaceProxy<'mesh> {
     fn is_valid(&self) -> bool {
         self.element().is_some()
@@ -122,6 +244,12 @@ impl<'mesh> HalfEdgeProxy<'mesh> {
         VertexProxy::maybe(vertex_handle, self.mesh)
     }
 
+    /// Returns the undirected edge this half-edge is part of.
+    pub fn full_edge(&self) -> FullEdgeProxy<'mesh> {
+        let adjacent_handle = self.data().map(|data| data.adjacent).unwrap_or_default();
+        FullEdgeProxy::new(FullEdgeHandle::new(self.handle, adjacent_handle), self.mesh)
+    }
+
     pub fn connect_to(&self, next: &HalfEdgeProxy) {
         log::trace!(
             "--- Connecting Edges {} -> v{} -> {}",
@@ -147,12 +275,136 @@ impl<'mesh> HalfEdgeProxy<'mesh> {
     }
 }
 
+impl<'mesh> HalfEdgeProxy<'mesh> {
+    /// Vector from the start to the end point of the half-edge.
+    pub fn direction(&self) -> Normal {
+        let start = self.vertex().point().position();
+        let end = self.adjacent().vertex().point().position();
+        end - start
+    }
+
+    pub fn length(&self) -> f32 {
+        self.direction().norm()
+    }
+
+    /// Angle between the normals of the faces on either side, zero where
+    /// the surface is flat or on the boundary.
+    pub fn dihedral_angle(&self) -> f32 {
+        if self.is_boundary() {
+            return 0.0;
+        }
+        let n0 = self.face().normal();
+        let n1 = self.adjacent().face().normal();
+        n0.dot(&n1).clamp(-1.0, 1.0).acos()
+    }
+
+    /// Angle opposite to the half-edge in the triangle spanned with the
+    /// corner preceding it in its face, the opposite corner of triangles.
+    ///
+    /// Returns `None` for half-edges without a face.
+    pub fn opposite_angle(&self) -> Option<f32> {
+        if !self.face().is_valid() {
+            return None;
+        }
+        let corner = self.prev().vertex().point().position();
+        let a = self.vertex().point().position();
+        let b = self.adjacent().vertex().point().position();
+        Some((a - corner).angle(&(b - corner)))
+    }
+
+    /// Weight of the edge in the cotangent Laplacian, half the sum of the
+    /// cotangents of the opposite angles on either side.
+    pub fn cotan_weight(&self) -> f32 {
+        let twin = self.adjacent();
+        let a = self.vertex().point().position();
+        let b = twin.vertex().point().position();
+        let mut weight = 0.0;
+        for side in [*self, twin].iter() {
+            if side.face().is_valid() {
+                let corner = side.prev().vertex().point().position();
+                weight += 0.5 * cotangent(&corner, &a, &b);
+            }
+        }
+        weight
+    }
+}
+
 impl<'mesh> IsValid for HalfEdgeProxy<'mesh> {
     fn is_valid(&self) -> bool {
         self.element().is_some()
     }
 }
 
+/// Function set for operations on an undirected edge.
+#[derive(Debug, Copy, Clone)]
+pub struct FullEdgeProxy<'mesh> {
+    mesh: &'mesh Mesh,
+    pub handle: FullEdgeHandle,
+}
+
+impl<'mesh> FullEdgeProxy<'mesh> {
+    pub fn new(handle: FullEdgeHandle, mesh: &'mesh Mesh) -> Self {
+        FullEdgeProxy { mesh, handle }
+    }
+
+    /// The half-edge identifying this full edge.
+    pub fn half_edge(&self) -> HalfEdgeProxy<'mesh> {
+        HalfEdgeProxy::new(self.handle.half_edge(), self.mesh)
+    }
+
+    /// Both half-edges, starting with the one identifying this full edge.
+    pub fn half_edges(&self) -> (HalfEdgeProxy<'mesh>, HalfEdgeProxy<'mesh>) {
+        let edge = self.half_edge();
+        (edge, edge.adjacent())
+    }
+
+    /// The faces on either side, which might be invalid for boundary edges.
+    pub fn faces(&self) -> (FaceProxy<'mesh>, FaceProxy<'mesh>) {
+        let (e0, e1) = self.half_edges();
+        (e0.face(), e1.face())
+    }
+
+    /// The vertices each half-edge starts at.
+    pub fn vertices(&self) -> (VertexProxy<'mesh>, VertexProxy<'mesh>) {
+        let (e0, e1) = self.half_edges();
+        (e0.vertex(), e1.vertex())
+    }
+
+    /// The points at either end of this edge.
+    pub fn points(&self) -> (PointProxy<'mesh>, PointProxy<'mesh>) {
+        let (v0, v1) = self.vertices();
+        (v0.point(), v1.point())
+    }
+
+    pub fn is_boundary(&self) -> bool {
+        self.half_edge().is_boundary()
+    }
+
+    /// Returns the crease sharpness of this edge.
+    pub fn crease(&self) -> f32 {
+        let (e0, e1) = self.half_edges();
+        let c0 = e0.data().map(|data| data.crease).unwrap_or(0.0);
+        let c1 = e1.data().map(|data| data.crease).unwrap_or(0.0);
+        c0.max(c1)
+    }
+
+    /// Sets the crease sharpness on both half-edges.
+    pub fn set_crease(&self, crease: f32) {
+        let (e0, e1) = self.half_edges();
+        for edge in [e0, e1].iter() {
+            if let Some(mut data) = edge.data_mut() {
+                data.crease = crease;
+            }
+        }
+    }
+}
+
+impl<'mesh> IsValid for FullEdgeProxy<'mesh> {
+    fn is_valid(&self) -> bool {
+        self.half_edge().is_valid()
+    }
+}
+
 /// Function set for operations related to the Vertex struct
 #[derive(Debug, Copy, Clone)]
 pub struct VertexProxy<'mesh> {
@@ -176,16 +428,99 @@ impl<'mesh> VertexProxy<'mesh> {
         HalfEdgeProxy::maybe(edge_handle, self.mesh)
     }
 
-    pub fn edges(&self) -> VertexCirculator {
+    /// Outgoing half-edges of all vertices at the point of this vertex.
+    pub fn edges(&self) -> VertexCirculator<'mesh> {
         VertexCirculator::new(self.mesh.next_tag(), *self)
     }
 
+    /// Faces around the point of this vertex.
+    pub fn faces(&self) -> VertexFaceCirculator<'mesh> {
+        VertexFaceCirculator::new(*self)
+    }
+
     pub fn point(&self) -> PointProxy<'mesh> {
         let point_handle = self.data().map(|data| data.point);
         PointProxy::maybe(point_handle, self.mesh)
     }
 }
 
+impl<'mesh> VertexProxy<'mesh> {
+    /// Number of edges leaving the point of this vertex.
+    pub fn valence(&self) -> usize {
+        self.edges().count()
+    }
+
+    /// The corners of the faces around the point, as the point itself and
+    /// its neighbors before and after it in each face.
+    fn corners(&self) -> Vec<(Position, Position, Position)> {
+        self.edges()
+            .filter(|edge| edge.face().is_valid())
+            .map(|edge| {
+                (
+                    edge.vertex().point().position(),
+                    edge.adjacent().vertex().point().position(),
+                    edge.prev().vertex().point().position(),
+                )
+            })
+            .collect()
+    }
+
+    /// Mixed Voronoi area of the point (Meyer et al.).
+    ///
+    /// Each face contributes the part of its corner closer to the point than
+    /// to the neighbors, or a fixed share of its area for obtuse triangles.
+    /// Larger faces only count the triangle at the corner.
+    pub fn voronoi_area(&self) -> f32 {
+        self.corners()
+            .iter()
+            .map(|(p, q, r)| {
+                let area = (q - p).cross(&(r - p)).norm() * 0.5;
+                if (q - p).dot(&(r - p)) < 0.0 {
+                    area * 0.5
+                } else if (p - q).dot(&(r - q)) < 0.0 || (p - r).dot(&(q - r)) < 0.0 {
+                    area * 0.25
+                } else {
+                    ((r - p).norm_squared() * cotangent(q, p, r)
+                        + (q - p).norm_squared() * cotangent(r, p, q))
+                        / 8.0
+                }
+            })
+            .sum()
+    }
+
+    /// Discrete Gaussian curvature as the difference between a full turn,
+    /// or half of it on the boundary, and the angles at the point.
+    pub fn angle_defect(&self) -> f32 {
+        let full = if self.edges().any(|edge| edge.is_boundary()) {
+            std::f32::consts::PI
+        } else {
+            std::f32::consts::PI * 2.0
+        };
+        let angles: f32 = self
+            .corners()
+            .iter()
+            .map(|(p, q, r)| (q - p).angle(&(r - p)))
+            .sum();
+        full - angles
+    }
+
+    /// Discrete mean curvature normal from the cotangent Laplacian, whose
+    /// length is twice the mean curvature.
+    ///
+    /// Points away from the center of convex surfaces, zero where the
+    /// Voronoi area vanishes.
+    pub fn mean_curvature_normal(&self) -> Normal {
+        let area = self.voronoi_area();
+        if area <= f32::EPSILON {
+            return na::zero();
+        }
+        let sum = self.edges().fold(na::zero(), |sum: Normal, edge| {
+            sum - edge.direction() * edge.cotan_weight()
+        });
+        sum / area
+    }
+}
+
 impl<'mesh> IsValid for VertexProxy<'mesh> {
     fn is_valid(&self) -> bool {
         self.element().is_some()
@@ -208,8 +543,171 @@ impl<'mesh> ElementProxy<'mesh, Point> for PointProxy<'mesh> {
     }
 }
 
+impl<'mesh> PointProxy<'mesh> {
+    /// Returns the position of this point, or the origin for invalid handles.
+    pub fn position(&self) -> Position {
+        self.data()
+            .map(|data| data.position)
+            .unwrap_or_else(Position::origin)
+    }
+}
+
 impl<'mesh> IsValid for PointProxy<'mesh> {
     fn is_valid(&self) -> bool {
         self.element().is_some()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    fn single_face(positions: &[Position]) -> Mesh {
+        let polygon: Vec<usize> = (0..positions.len()).collect();
+        Mesh::from_polygons(positions, &[polygon])
+    }
+
+    #[test]
+    fn can_measure_faces() {
+        let _ = env_logger::try_init();
+        let mesh = single_face(&[
+            Position::new(0.0, 0.0, 0.0),
+            Position::new(2.0, 0.0, 0.0),
+            Position::new(2.0, 1.0, 0.0),
+            Position::new(0.0, 1.0, 0.0),
+        ]);
+        let face = mesh.faces().next().unwrap();
+        assert!((face.area() - 2.0).abs() < 1e-6);
+        assert!((face.perimeter() - 6.0).abs() < 1e-6);
+        assert_eq!(face.normal(), Normal::new(0.0, 0.0, 1.0));
+        assert_eq!(face.centroid(), Position::new(1.0, 0.5, 0.0));
+        assert!(face.is_planar(0.0));
+        assert!(face.is_convex());
+        assert_eq!(face.aspect_ratio(), None);
+    }
+
+    #[test]
+    fn centroid_is_weighted_by_area() {
+        let _ = env_logger::try_init();
+        // An L shape made of three unit squares.
+        let mesh = single_face(&[
+            Position::new(0.0, 0.0, 0.0),
+            Position::new(2.0, 0.0, 0.0),
+            Position::new(2.0, 1.0, 0.0),
+            Position::new(1.0, 1.0, 0.0),
+            Position::new(1.0, 2.0, 0.0),
+            Position::new(0.0, 2.0, 0.0),
+        ]);
+        let face = mesh.faces().next().unwrap();
+        assert!((face.area() - 3.0).abs() < 1e-6);
+        let expected = Position::new(5.0 / 6.0, 5.0 / 6.0, 0.0);
+        assert!((face.centroid() - expected).norm() < 1e-6);
+        assert!(!face.is_convex());
+    }
+
+    #[test]
+    fn detects_non_planar_faces() {
+        let _ = env_logger::try_init();
+        let mesh = single_face(&[
+            Position::new(0.0, 0.0, 0.0),
+            Position::new(1.0, 0.0, 0.0),
+            Position::new(1.0, 1.0, 0.5),
+            Position::new(0.0, 1.0, 0.0),
+        ]);
+        let face = mesh.faces().next().unwrap();
+        assert!(!face.is_planar(0.01));
+        assert!(face.is_planar(0.2));
+    }
+
+    #[test]
+    fn can_rate_triangle_shapes() {
+        let _ = env_logger::try_init();
+        let equilateral = single_face(&[
+            Position::new(0.0, 0.0, 0.0),
+            Position::new(1.0, 0.0, 0.0),
+            Position::new(0.5, 0.75f32.sqrt(), 0.0),
+        ]);
+        let ratio = equilateral.faces().next().unwrap().aspect_ratio().unwrap();
+        assert!((ratio - 1.0).abs() < 1e-5);
+
+        let sliver = single_face(&[
+            Position::new(0.0, 0.0, 0.0),
+            Position::new(1.0, 0.0, 0.0),
+            Position::new(0.5, 0.01, 0.0),
+        ]);
+        let ratio = sliver.faces().next().unwrap().aspect_ratio().unwrap();
+        assert!(ratio > 10.0);
+    }
+
+    #[test]
+    fn can_measure_edges() {
+        let _ = env_logger::try_init();
+        let mesh = Mesh::unit_cube();
+        for edge in mesh.edges() {
+            assert!((edge.length() - 1.0).abs() < 1e-6);
+            assert!((edge.dihedral_angle() - std::f32::consts::FRAC_PI_2).abs() < 1e-5);
+            assert!((edge.opposite_angle().unwrap() - std::f32::consts::FRAC_PI_4).abs() < 1e-5);
+        }
+
+        let mesh = single_face(&[
+            Position::new(0.0, 0.0, 0.0),
+            Position::new(2.0, 0.0, 0.0),
+            Position::new(0.0, 1.0, 0.0),
+        ]);
+        let edge = mesh
+            .edges()
+            .find(|e| e.face().is_valid() && e.vertex().point().position().x == 2.0)
+            .unwrap();
+        assert_eq!(edge.direction(), Normal::new(-2.0, 1.0, 0.0));
+        assert_eq!(edge.dihedral_angle(), 0.0);
+        assert!((edge.opposite_angle().unwrap() - std::f32::consts::FRAC_PI_2).abs() < 1e-6);
+        assert!(edge.cotan_weight().abs() < 1e-6);
+        assert_eq!(edge.adjacent().opposite_angle(), None);
+        let hypotenuse = edge.next();
+        assert!((hypotenuse.cotan_weight() - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn can_measure_vertices_of_sphere() {
+        let _ = env_logger::try_init();
+        let sphere = Mesh::icosphere(1.0, 2);
+        let mut visited = std::collections::HashSet::new();
+        let (mut defect, mut area) = (0.0, 0.0);
+        for vert in sphere.vertices() {
+            if !visited.insert(vert.point().handle.index()) {
+                continue;
+            }
+            let valence = vert.valence();
+            assert!(valence == 5 || valence == 6);
+            defect += vert.angle_defect();
+            area += vert.voronoi_area();
+
+            let position = vert.point().position().coords;
+            let curvature = vert.mean_curvature_normal();
+            assert!((curvature.norm() - 2.0).abs() < 0.1);
+            assert!(curvature.normalize().dot(&position) > 0.99);
+        }
+        let total: f32 = sphere.faces().map(|face| face.area()).sum();
+        assert!((defect - std::f32::consts::PI * 4.0).abs() < 1e-3);
+        assert!((area - total).abs() < 1e-3);
+    }
+
+    #[test]
+    fn flat_points_have_no_curvature() {
+        let _ = env_logger::try_init();
+        let mut mesh = Mesh::grid(2.0, 2.0, 2, 2);
+        mesh.triangulate(Triangulation::Fan);
+        let center = mesh
+            .vertices()
+            .find(|v| v.point().position() == Position::origin())
+            .unwrap();
+        assert!(center.angle_defect().abs() < 1e-5);
+        assert!(center.mean_curvature_normal().norm() < 1e-5);
+        assert!((center.voronoi_area() - 1.0).abs() < 1e-5);
+        let corner = mesh
+            .vertices()
+            .find(|v| v.point().position() == Position::new(1.0, 0.0, 1.0))
+            .unwrap();
+        assert!((corner.angle_defect() - std::f32::consts::FRAC_PI_2).abs() < 1e-5);
+    }
+}
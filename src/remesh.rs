@@ -0,0 +1,285 @@
+//! Isotropic remeshing towards a uniform edge length.
+
+use std::collections::HashMap;
+
+use crate::data::{Normal, Position, Triangulation};
+use crate::handles::{HalfEdgeHandle, PointHandle, VertexHandle};
+use crate::mesh::Mesh;
+use crate::proxy::*;
+use crate::traits::*;
+use nalgebra as na;
+
+fn is_boundary_point(vert: &VertexProxy) -> bool {
+    vert.edges().any(|edge| edge.is_boundary())
+}
+
+/// Whether the boundary turns at the point, or the point belongs to a
+/// single face. Such points shape the outline and are never collapsed.
+fn is_corner_point(vert: &VertexProxy) -> bool {
+    let position = vert.point().position();
+    let boundary: Vec<_> = vert
+        .edges()
+        .filter(|edge| edge.is_boundary())
+        .map(|edge| edge.adjacent().vertex().point().position() - position)
+        .collect();
+    let faces = vert.edges().filter(|edge| edge.face().is_valid()).count();
+    if faces <= 1 {
+        return true;
+    }
+    match boundary.as_slice() {
+        [d0, d1] => match (
+            d0.try_normalize(f32::EPSILON),
+            d1.try_normalize(f32::EPSILON),
+        ) {
+            (Some(d0), Some(d1)) => d0.dot(&d1) > -0.999,
+            _ => true,
+        },
+        _ => !boundary.is_empty(),
+    }
+}
+
+/// Squared difference of the valence of a point, changed by `change`, to
+/// its ideal valence, which is lower on the boundary.
+fn valence_deviation(vert: &VertexProxy, change: isize) -> isize {
+    let target = if is_boundary_point(vert) { 4 } else { 6 };
+    let valence = vert.edges().count() as isize + change;
+    (valence - target).pow(2)
+}
+
+/// One vertex for every point of the mesh.
+fn point_vertices(mesh: &Mesh) -> Vec<VertexHandle> {
+    let mut vertices: HashMap<PointHandle, VertexHandle> = HashMap::new();
+    for vert in mesh.vertices() {
+        vertices.entry(vert.point().handle).or_insert(vert.handle);
+    }
+    vertices.values().cloned().collect()
+}
+
+fn split_long_edges(mesh: &mut Mesh, high: f32) {
+    loop {
+        let long_edges: Vec<HalfEdgeHandle> = mesh
+            .full_edges()
+            .map(|edge| edge.half_edge())
+            .filter(|edge| edge.length() > high)
+            .map(|edge| edge.handle)
+            .collect();
+        if long_edges.is_empty() {
+            break;
+        }
+        for edge in long_edges {
+            let (p0, p1) = mesh.edge(edge).full_edge().points();
+            let midpoint = Position::from((p0.position().coords + p1.position().coords) * 0.5);
+            let _ = mesh.split_edge(edge, midpoint);
+        }
+    }
+}
+
+fn collapse_short_edges(mesh: &mut Mesh, low: f32, high: f32) {
+    let short_edges: Vec<HalfEdgeHandle> = mesh
+        .full_edges()
+        .map(|edge| edge.half_edge())
+        .filter(|edge| edge.length() < low)
+        .map(|edge| edge.handle)
+        .collect();
+    for edge in short_edges {
+        let edge = mesh.edge(edge);
+        if !edge.is_valid() || edge.length() >= low {
+            continue;
+        }
+        let twin = edge.adjacent();
+        let (a_boundary, b_boundary) = (
+            is_boundary_point(&edge.vertex()),
+            is_boundary_point(&twin.vertex()),
+        );
+        // The kept point is the start of the collapsed half-edge, which
+        // has to be on the boundary if any of the two is.
+        let (edge, position) = match (a_boundary, b_boundary) {
+            (false, false) => {
+                let (p0, p1) = edge.full_edge().points();
+                let midpoint = (p0.position().coords + p1.position().coords) * 0.5;
+                (edge, Position::from(midpoint))
+            }
+            (false, true) => (twin, twin.vertex().point().position()),
+            (true, false) => (edge, edge.vertex().point().position()),
+            (true, true) => match (
+                is_corner_point(&edge.vertex()),
+                is_corner_point(&twin.vertex()),
+            ) {
+                (_, false) => (edge, edge.vertex().point().position()),
+                (false, true) => (twin, twin.vertex().point().position()),
+                (true, true) => continue,
+            },
+        };
+        let stretched = edge
+            .vertex()
+            .edges()
+            .chain(edge.adjacent().vertex().edges())
+            .any(|e| (e.adjacent().vertex().point().position() - position).norm() > high);
+        if stretched || !mesh.is_collapse_ok(edge.handle) {
+            continue;
+        }
+        let point = edge.vertex().point().handle;
+        let edge = edge.handle;
+        if mesh.collapse_edge(edge).is_some() {
+            if let Some(point) = mesh.get(point) {
+                point.data_mut().position = position;
+            }
+        }
+    }
+}
+
+fn equalize_valences(mesh: &mut Mesh) {
+    let edges: Vec<HalfEdgeHandle> = mesh
+        .full_edges()
+        .filter(|edge| !edge.is_boundary())
+        .map(|edge| edge.half_edge().handle)
+        .collect();
+    for edge in edges {
+        if !mesh.is_flip_ok(edge) {
+            continue;
+        }
+        let edge = mesh.edge(edge);
+        let twin = edge.adjacent();
+        let corners = [
+            (edge.vertex(), -1),
+            (twin.vertex(), -1),
+            (edge.prev().vertex(), 1),
+            (twin.prev().vertex(), 1),
+        ];
+        let before: isize = corners
+            .iter()
+            .map(|(vert, _)| valence_deviation(vert, 0))
+            .sum();
+        let after: isize = corners
+            .iter()
+            .map(|(vert, change)| valence_deviation(vert, *change))
+            .sum();
+        if after < before {
+            let edge = edge.handle;
+            let _ = mesh.flip_edge(edge);
+        }
+    }
+}
+
+/// Moves every interior point towards the average of its neighbors,
+/// restricted to the tangent plane at the point.
+fn tangential_relaxation(mesh: &mut Mesh) {
+    let moved: Vec<_> = point_vertices(mesh)
+        .into_iter()
+        .map(|vert| mesh.vertex(vert))
+        .filter(|vert| !is_boundary_point(vert))
+        .filter_map(|vert| {
+            let position = vert.point().position();
+            let edges: Vec<_> = vert.edges().collect();
+            if edges.is_empty() {
+                return None;
+            }
+            let mut centroid: na::Vector3<f32> = na::zero();
+            let mut normal: Normal = na::zero();
+            for edge in edges.iter() {
+                let p1 = edge.adjacent().vertex().point().position();
+                let p2 = edge.next().adjacent().vertex().point().position();
+                centroid += p1.coords;
+                normal += (p1 - position).cross(&(p2 - position));
+            }
+            let centroid = Position::from(centroid / edges.len() as f32);
+            let normal = normal.try_normalize(f32::EPSILON)?;
+            let offset = position - centroid;
+            Some((vert.point().handle, centroid + normal * normal.dot(&offset)))
+        })
+        .collect();
+    for (point, position) in moved {
+        if let Some(point) = mesh.get(point) {
+            point.data_mut().position = position;
+        }
+    }
+}
+
+/// Remeshes the surface into triangles with edges close to `target_length`.
+///
+/// Each iteration splits edges longer than 4/3 of the target length,
+/// collapses edges shorter than 4/5 of it, flips edges to bring the valence
+/// of the points closer to 6 (4 on the boundary) and finally relaxes the
+/// points within their tangent plane. Faces with more than three corners
+/// are triangulated first. Boundary points are never moved off the
+/// boundary, and points aren't projected back onto the original surface.
+pub fn remesh(mesh: &mut Mesh, target_length: f32, iterations: usize) {
+    let (low, high) = (target_length * 0.8, target_length * 4.0 / 3.0);
+    mesh.triangulate(Triangulation::ShortestDiagonal);
+    for _ in 0..iterations {
+        split_long_edges(mesh, high);
+        collapse_short_edges(mesh, low, high);
+        equalize_valences(mesh);
+        tangential_relaxation(mesh);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::*;
+    use crate::*;
+
+    fn mean_edge_length(mesh: &Mesh) -> f32 {
+        let lengths: Vec<f32> = mesh
+            .full_edges()
+            .map(|edge| {
+                let (p0, p1) = edge.points();
+                (p1.position() - p0.position()).norm()
+            })
+            .collect();
+        lengths.iter().sum::<f32>() / lengths.len() as f32
+    }
+
+    #[test]
+    fn remeshing_refines_sphere() {
+        let _ = env_logger::try_init();
+        let mut mesh = Mesh::icosphere(1.0, 1);
+        remesh(&mut mesh, 0.2, 5);
+        assert!(mesh.is_valid());
+        assert!(mesh.face_count() > 200);
+        assert!(mesh.faces().all(|f| f.vertices().count() == 3));
+        assert_eq!(euler_characteristic(&mesh), 2);
+        let length = mean_edge_length(&mesh);
+        assert!(length > 0.15 && length < 0.25);
+    }
+
+    #[test]
+    fn remeshing_coarsens_sphere() {
+        let _ = env_logger::try_init();
+        let mut mesh = Mesh::icosphere(1.0, 3);
+        remesh(&mut mesh, 0.5, 5);
+        assert!(mesh.is_valid());
+        assert!(mesh.face_count() < 200);
+        assert_eq!(euler_characteristic(&mesh), 2);
+        let length = mean_edge_length(&mesh);
+        assert!(length > 0.35 && length < 0.65);
+    }
+
+    #[test]
+    fn remeshing_keeps_boundary() {
+        let _ = env_logger::try_init();
+        // Refining a coarse grid and coarsening a fine one.
+        for (divisions, target) in [(2, 0.1), (10, 0.3)].iter() {
+            let mut mesh = Mesh::grid(1.0, 1.0, *divisions, *divisions);
+            remesh(&mut mesh, *target, 3);
+            assert!(mesh.is_valid());
+            assert!(mesh.faces().all(|f| f.vertices().count() == 3));
+            for corner in [(-0.5, -0.5), (-0.5, 0.5), (0.5, -0.5), (0.5, 0.5)].iter() {
+                let corner = Position::new(corner.0, 0.0, corner.1);
+                assert!(mesh.points().any(|p| (p.position() - corner).norm() < 1e-6));
+            }
+            for vert in mesh.vertices() {
+                let position = vert.point().position();
+                assert!(position.y.abs() < 1e-6);
+                if vert.edges().any(|edge| edge.is_boundary()) {
+                    assert!(
+                        (position.x.abs() - 0.5).abs() < 1e-6
+                            || (position.z.abs() - 0.5).abs() < 1e-6
+                    );
+                }
+            }
+            let length = mean_edge_length(&mesh);
+            assert!(length > target * 0.7 && length < target * 1.3);
+        }
+    }
+}
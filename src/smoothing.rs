@@ -0,0 +1,272 @@
+//! Laplacian smoothing of point positions.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::data::{Position, SmoothingOptions, Weighting};
+use crate::handles::{PointHandle, VertexHandle};
+use crate::mesh::Mesh;
+use crate::proxy::*;
+use crate::traits::*;
+use nalgebra as na;
+
+/// Outgoing half-edges of a point, gathered from all of its vertices so
+/// points shared by several fans of faces are fully covered.
+fn outgoing_edges<'mesh>(
+    mesh: &'mesh Mesh,
+    vertices: &[VertexHandle],
+) -> Vec<HalfEdgeProxy<'mesh>> {
+    let mut visited = HashSet::new();
+    let mut edges = Vec::new();
+    for vert in vertices {
+        let vert = mesh.vertex(*vert);
+        if visited.contains(&vert.edge().handle.index()) {
+            continue;
+        }
+        for edge in vert.edges() {
+            if visited.insert(edge.handle.index()) {
+                edges.push(edge);
+            }
+        }
+    }
+    edges
+}
+
+/// Weighted neighbors of every point which is allowed to move.
+fn one_rings(mesh: &Mesh, options: &SmoothingOptions) -> Vec<(PointHandle, Vec<(Position, f32)>)> {
+    let mut vertices: HashMap<PointHandle, Vec<VertexHandle>> = HashMap::new();
+    for vert in mesh.vertices() {
+        vertices
+            .entry(vert.point().handle)
+            .or_default()
+            .push(vert.handle);
+    }
+
+    let mut rings = Vec::with_capacity(vertices.len());
+    for (point, vertices) in vertices {
+        let pinned = options.pinned_tag.map_or(false, |tag| {
+            mesh.point(point)
+                .element()
+                .map_or(false, |element| element.tag() == tag)
+        });
+        if pinned {
+            continue;
+        }
+        let edges = outgoing_edges(mesh, &vertices);
+        let on_boundary = edges.iter().any(|edge| edge.is_boundary());
+        if on_boundary && options.pin_boundary {
+            continue;
+        }
+
+        let neighbors = edges
+            .iter()
+            .filter(|edge| !on_boundary || edge.is_boundary())
+            .map(|edge| {
+                let weight = match options.weighting {
+                    Weighting::Cotangent if !on_boundary => edge.cotan_weight().max(0.0),
+                    _ => 1.0,
+                };
+                (edge.adjacent().vertex().point().position(), weight)
+            })
+            .collect();
+        rings.push((point, neighbors));
+    }
+    rings
+}
+
+/// Moves every point by `factor` times its Laplacian.
+fn laplacian_step(mesh: &mut Mesh, factor: f32, options: &SmoothingOptions) {
+    let moved: Vec<_> = one_rings(mesh, options)
+        .into_iter()
+        .filter_map(|(point, neighbors)| {
+            let total: f32 = neighbors.iter().map(|(_, weight)| weight).sum();
+            if neighbors.is_empty() {
+                return None;
+            }
+            let position = mesh.point(point).position();
+            let mut laplacian: na::Vector3<f32> = na::zero();
+            for (neighbor, weight) in neighbors.iter() {
+                let weight = if total > f32::EPSILON {
+                    weight / total
+                } else {
+                    1.0 / neighbors.len() as f32
+                };
+                laplacian += (neighbor - position) * weight;
+            }
+            Some((point, position + laplacian * factor))
+        })
+        .collect();
+
+    for (point, position) in moved {
+        if let Some(point) = mesh.get(point) {
+            point.data_mut().position = position;
+        }
+    }
+}
+
+/// Moves every point towards the weighted average of its neighbors.
+///
+/// `lambda` is the fraction of the way each step moves, usually in `(0, 1]`.
+/// Repeated smoothing shrinks the mesh, see `smooth_taubin`.
+pub fn smooth_laplacian(mesh: &mut Mesh, lambda: f32, options: &SmoothingOptions) {
+    for _ in 0..options.iterations {
+        laplacian_step(mesh, lambda, options);
+    }
+}
+
+/// Taubin λ/μ smoothing, alternating a shrinking step with `lambda` and
+/// an inflating step with the negative `mu`.
+///
+/// `mu` should be slightly larger in magnitude than `lambda`, for example
+/// `lambda = 0.5` and `mu = -0.53`.
+pub fn smooth_taubin(mesh: &mut Mesh, lambda: f32, mu: f32, options: &SmoothingOptions) {
+    for _ in 0..options.iterations {
+        laplacian_step(mesh, lambda, options);
+        laplacian_step(mesh, mu, options);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    fn mean_radius(mesh: &Mesh) -> f32 {
+        let radii: Vec<f32> = mesh
+            .vertices()
+            .map(|vert| vert.point().position().coords.norm())
+            .collect();
+        radii.iter().sum::<f32>() / radii.len() as f32
+    }
+
+    fn noisy_grid() -> Mesh {
+        let mesh = Mesh::grid(1.0, 1.0, 6, 6);
+        for (index, point) in mesh.kernel.point_buffer.active_elements().enumerate() {
+            let mut data = point.data_mut();
+            if data.position.x.abs() < 0.5 && data.position.z.abs() < 0.5 {
+                data.position.y = if index % 2 == 0 { 0.1 } else { -0.1 };
+            }
+        }
+        mesh
+    }
+
+    #[test]
+    fn laplacian_smoothing_flattens_noise() {
+        let _ = env_logger::try_init();
+        let mut mesh = noisy_grid();
+        let options = SmoothingOptions {
+            iterations: 20,
+            ..Default::default()
+        };
+        smooth_laplacian(&mut mesh, 0.5, &options);
+        assert!(mesh.is_valid());
+        for vert in mesh.vertices() {
+            let position = vert.point().position();
+            assert!(position.y.abs() < 0.01);
+            if vert.edges().any(|edge| edge.is_boundary()) {
+                assert!(position.x.abs() == 0.5 || position.z.abs() == 0.5);
+            }
+        }
+    }
+
+    #[test]
+    fn boundary_moves_along_itself_when_not_pinned() {
+        let _ = env_logger::try_init();
+        let mut mesh = noisy_grid();
+        let options = SmoothingOptions {
+            iterations: 5,
+            pin_boundary: false,
+            ..Default::default()
+        };
+        smooth_laplacian(&mut mesh, 0.5, &options);
+        assert!(mesh.is_valid());
+        let boundary: Vec<_> = mesh
+            .vertices()
+            .filter(|vert| vert.edges().any(|edge| edge.is_boundary()))
+            .map(|vert| vert.point().position())
+            .collect();
+        assert!(boundary.iter().all(|p| p.y == 0.0));
+        assert!(boundary
+            .iter()
+            .all(|p| p.x.abs() <= 0.5 && p.z.abs() <= 0.5));
+        assert!(!boundary
+            .iter()
+            .any(|p| p.x.abs() == 0.5 && p.z.abs() == 0.5));
+    }
+
+    #[test]
+    fn tagged_points_are_pinned() {
+        let _ = env_logger::try_init();
+        let mut mesh = Mesh::icosphere(1.0, 1);
+        let tag = mesh.next_tag();
+        let pinned: Vec<_> = mesh
+            .kernel
+            .point_buffer
+            .active_elements()
+            .step_by(3)
+            .map(|point| {
+                point.set_tag(tag);
+                point.data().position
+            })
+            .collect();
+
+        let options = SmoothingOptions {
+            iterations: 3,
+            pinned_tag: Some(tag),
+            ..Default::default()
+        };
+        smooth_laplacian(&mut mesh, 1.0, &options);
+        let positions: Vec<_> = mesh
+            .kernel
+            .point_buffer
+            .active_elements()
+            .step_by(3)
+            .map(|point| point.data().position)
+            .collect();
+        assert_eq!(pinned, positions);
+    }
+
+    #[test]
+    fn taubin_smoothing_avoids_shrinkage() {
+        let _ = env_logger::try_init();
+        let options = SmoothingOptions {
+            iterations: 10,
+            ..Default::default()
+        };
+
+        let mut laplacian = Mesh::icosphere(1.0, 2);
+        smooth_laplacian(&mut laplacian, 0.5, &options);
+        let mut taubin = Mesh::icosphere(1.0, 2);
+        smooth_taubin(&mut taubin, 0.5, -0.53, &options);
+
+        assert!(mean_radius(&laplacian) < 0.95);
+        assert!((mean_radius(&taubin) - 1.0).abs() < 0.02);
+    }
+
+    #[test]
+    fn cotangent_laplacian_vanishes_on_planes() {
+        let _ = env_logger::try_init();
+        let mut positions = vec![Position::new(0.2, 0.1, 0.0)];
+        for index in 0..6 {
+            let angle = index as f32 * std::f32::consts::PI / 3.0;
+            positions.push(Position::new(angle.cos(), angle.sin(), 0.0));
+        }
+        let polygons: Vec<_> = (0..6).map(|i| [0, i + 1, (i + 1) % 6 + 1]).collect();
+
+        let options = SmoothingOptions {
+            weighting: Weighting::Cotangent,
+            ..Default::default()
+        };
+        let mut mesh = Mesh::from_polygons(&positions, &polygons);
+        smooth_laplacian(&mut mesh, 1.0, &options);
+        let center = mesh.point(PointHandle::new(1)).position();
+        assert!((center - positions[0]).norm() < 1e-5);
+
+        let options = SmoothingOptions {
+            weighting: Weighting::Uniform,
+            ..options
+        };
+        let mut mesh = Mesh::from_polygons(&positions, &polygons);
+        smooth_laplacian(&mut mesh, 1.0, &options);
+        let center = mesh.point(PointHandle::new(1)).position();
+        assert!(center.coords.norm() < 1e-5);
+    }
+}
@@ -0,0 +1,864 @@
+//! Subdivision schemes producing refined copies of a mesh.
+
+use std::collections::{BTreeMap, HashMap};
+use std::f32::consts::PI;
+
+use crate::data::{Position, VertexAttributes};
+use crate::handles::PointHandle;
+use crate::mesh::Mesh;
+use crate::proxy::*;
+
+/// Key of an undirected edge between two polygon indices.
+type EdgeKey = (usize, usize);
+
+fn edge_key(a: usize, b: usize) -> EdgeKey {
+    (a.min(b), a.max(b))
+}
+
+/// Indexed copy of a mesh which the subdivision schemes operate on.
+///
+/// Attributes are stored per face corner so seams in the vertex
+/// attributes survive subdivision.
+#[derive(Debug, Clone, Default)]
+struct Polygons {
+    positions: Vec<Position>,
+    faces: Vec<Vec<usize>>,
+    attributes: Vec<Vec<VertexAttributes>>,
+    /// Sharpness of edges with a non zero crease.
+    creases: HashMap<EdgeKey, f32>,
+}
+
+impl Polygons {
+    fn from_mesh(mesh: &Mesh) -> Self {
+        let mut polygons = Polygons::default();
+        let mut indices: HashMap<PointHandle, usize> = HashMap::new();
+        let mut creases = Vec::new();
+        for face in mesh.faces() {
+            let mut corners = Vec::new();
+            let mut attributes = Vec::new();
+            for edge in face.edges() {
+                let crease = edge.full_edge().crease();
+                if crease > 0.0 {
+                    let p0 = edge.vertex().point().handle;
+                    let p1 = edge.adjacent().vertex().point().handle;
+                    creases.push((p0, p1, crease));
+                }
+            }
+            for vert in face.vertices() {
+                let point = vert.point();
+                let index = *indices.entry(point.handle).or_insert_with(|| {
+                    polygons.positions.push(point.position());
+                    polygons.positions.len() - 1
+                });
+                corners.push(index);
+                attributes.push(vert.data().map(|d| d.attrs.clone()).unwrap_or_default());
+            }
+            polygons.faces.push(corners);
+            polygons.attributes.push(attributes);
+        }
+        for (p0, p1, crease) in creases {
+            if let (Some(a), Some(b)) = (indices.get(&p0), indices.get(&p1)) {
+                polygons.creases.insert(edge_key(*a, *b), crease);
+            }
+        }
+        polygons
+    }
+
+    /// Splits every face with more than three corners into a fan of triangles.
+    fn triangulated(self) -> Self {
+        if self.faces.iter().all(|face| face.len() == 3) {
+            return self;
+        }
+        let mut faces = Vec::new();
+        let mut attributes = Vec::new();
+        for (face, attrs) in self.faces.iter().zip(self.attributes.iter()) {
+            for corner in 1..face.len() - 1 {
+                faces.push(vec![face[0], face[corner], face[corner + 1]]);
+                attributes.push(vec![
+                    attrs[0].clone(),
+                    attrs[corner].clone(),
+                    attrs[corner + 1].clone(),
+                ]);
+            }
+        }
+        Polygons {
+            positions: self.positions,
+            faces,
+            attributes,
+            creases: self.creases,
+        }
+    }
+
+    /// Maps each undirected edge to the faces using it, as pairs of
+    /// face index and the corner the edge starts at.
+    fn edges(&self) -> BTreeMap<EdgeKey, Vec<(usize, usize)>> {
+        let mut edges: BTreeMap<EdgeKey, Vec<(usize, usize)>> = BTreeMap::new();
+        for (face_index, face) in self.faces.iter().enumerate() {
+            for corner in 0..face.len() {
+                let key = edge_key(face[corner], face[(corner + 1) % face.len()]);
+                edges.entry(key).or_default().push((face_index, corner));
+            }
+        }
+        edges
+    }
+
+    fn into_mesh(self) -> Mesh {
+        let mesh = Mesh::from_polygons(&self.positions, &self.faces);
+        for ((face, corners), attributes) in mesh
+            .faces()
+            .zip(self.faces.iter())
+            .zip(self.attributes.iter())
+        {
+            for (vert, attrs) in face.vertices().zip(attributes.iter()) {
+                if let Some(mut data) = vert.data_mut() {
+                    data.attrs = attrs.clone();
+                }
+            }
+            if !self.creases.is_empty() {
+                for (corner, edge) in face.edges().enumerate() {
+                    let key = edge_key(corners[corner], corners[(corner + 1) % corners.len()]);
+                    if let Some(crease) = self.creases.get(&key) {
+                        edge.full_edge().set_crease(*crease);
+                    }
+                }
+            }
+        }
+        mesh
+    }
+}
+
+/// Loop's weight for the neighbors of an interior vertex of valence `n`.
+fn loop_beta(n: usize) -> f32 {
+    let n = n as f32;
+    let c = 3.0 / 8.0 + 0.25 * (2.0 * PI / n).cos();
+    (5.0 / 8.0 - c * c) / n
+}
+
+fn loop_step(polygons: &Polygons) -> Polygons {
+    let edges = polygons.edges();
+    let point_count = polygons.positions.len();
+    // Boundary and non-manifold edges are both treated as sharp creases.
+    let sharpness = |key: &EdgeKey, faces: &[(usize, usize)]| {
+        if faces.len() != 2 {
+            f32::INFINITY
+        } else {
+            polygons.creases.get(key).cloned().unwrap_or(0.0)
+        }
+    };
+    let mut point_edges = vec![Vec::new(); point_count];
+    for (&(a, b), faces) in &edges {
+        let crease = sharpness(&(a, b), faces);
+        point_edges[a].push((b, crease));
+        point_edges[b].push((a, crease));
+    }
+
+    let positions = &polygons.positions;
+    let mut refined = Polygons::default();
+    for (index, position) in positions.iter().enumerate() {
+        let incident = &point_edges[index];
+        let valence = incident.len();
+        let sharp: Vec<&(usize, f32)> = incident.iter().filter(|(_, c)| *c > 0.0).collect();
+        let smooth = || {
+            let beta = loop_beta(valence);
+            let sum = incident.iter().fold(position.coords * 0.0, |sum, (n, _)| {
+                sum + positions[*n].coords
+            });
+            Position::from(position.coords * (1.0 - valence as f32 * beta) + sum * beta)
+        };
+        let moved = match sharp.as_slice() {
+            _ if valence == 0 => *position,
+            [] | [_] => smooth(),
+            [(n0, c0), (n1, c1)] => {
+                let crease = crease_rule(position, &positions[*n0], &positions[*n1]);
+                let weight = ((c0 + c1) * 0.5).min(1.0);
+                if weight < 1.0 {
+                    Position::from(smooth().coords.lerp(&crease.coords, weight))
+                } else {
+                    crease
+                }
+            }
+            // Corners and non-manifold vertices stay in place.
+            _ => *position,
+        };
+        refined.positions.push(moved);
+    }
+
+    let mut edge_points: HashMap<EdgeKey, usize> = HashMap::new();
+    for (&(a, b), faces) in &edges {
+        let crease = sharpness(&(a, b), faces);
+        let midpoint = (positions[a].coords + positions[b].coords) * 0.5;
+        let position = if crease >= 1.0 {
+            midpoint
+        } else {
+            let opposite = faces.iter().fold(midpoint * 0.0, |sum, (face, corner)| {
+                let face = &polygons.faces[*face];
+                sum + positions[face[(corner + 2) % face.len()]].coords
+            });
+            (midpoint * 0.75 + opposite * 0.125).lerp(&midpoint, crease)
+        };
+        refined.positions.push(Position::from(position));
+        edge_points.insert((a, b), refined.positions.len() - 1);
+        if let Some(crease) = polygons.creases.get(&(a, b)).filter(|c| **c > 1.0) {
+            let child = refined.positions.len() - 1;
+            refined.creases.insert(edge_key(a, child), crease - 1.0);
+            refined.creases.insert(edge_key(b, child), crease - 1.0);
+        }
+    }
+
+    for (face, attrs) in polygons.faces.iter().zip(polygons.attributes.iter()) {
+        let mids: Vec<usize> = (0..3)
+            .map(|corner| edge_points[&edge_key(face[corner], face[(corner + 1) % 3])])
+            .collect();
+        let mid_attrs: Vec<VertexAttributes> = (0..3)
+            .map(|corner| {
+                VertexAttributes::blend(&[(&attrs[corner], 0.5), (&attrs[(corner + 1) % 3], 0.5)])
+            })
+            .collect();
+        for corner in 0..3 {
+            let previous = (corner + 2) % 3;
+            refined
+                .faces
+                .push(vec![face[corner], mids[corner], mids[previous]]);
+            refined.attributes.push(vec![
+                attrs[corner].clone(),
+                mid_attrs[corner].clone(),
+                mid_attrs[previous].clone(),
+            ]);
+        }
+        refined.faces.push(mids.clone());
+        refined.attributes.push(mid_attrs);
+    }
+
+    refined
+}
+
+fn centroid(positions: &[Position], face: &[usize]) -> Position {
+    let sum = face.iter().fold(positions[face[0]].coords * 0.0, |sum, i| {
+        sum + positions[*i].coords
+    });
+    Position::from(sum / face.len() as f32)
+}
+
+fn average_attributes(attrs: &[VertexAttributes]) -> VertexAttributes {
+    let weight = 1.0 / attrs.len() as f32;
+    let weighted: Vec<(&VertexAttributes, f32)> = attrs.iter().map(|a| (a, weight)).collect();
+    VertexAttributes::blend(&weighted)
+}
+
+fn crease_rule(position: &Position, n0: &Position, n1: &Position) -> Position {
+    Position::from(position.coords * 0.75 + (n0.coords + n1.coords) * 0.125)
+}
+
+fn catmull_clark_step(polygons: &Polygons) -> Polygons {
+    let edges = polygons.edges();
+    let positions = &polygons.positions;
+    let point_count = positions.len();
+    let sharpness = |key: &EdgeKey, faces: &[(usize, usize)]| {
+        if faces.len() != 2 {
+            f32::INFINITY
+        } else {
+            polygons.creases.get(key).cloned().unwrap_or(0.0)
+        }
+    };
+
+    let mut refined = Polygons::default();
+
+    // Face points are the centroids of the faces.
+    let face_points: Vec<Position> = polygons
+        .faces
+        .iter()
+        .map(|face| centroid(positions, face))
+        .collect();
+
+    let mut point_faces = vec![Vec::new(); point_count];
+    for (face_index, face) in polygons.faces.iter().enumerate() {
+        for corner in face {
+            point_faces[*corner].push(face_index);
+        }
+    }
+    let mut point_edges = vec![Vec::new(); point_count];
+    for (&(a, b), faces) in &edges {
+        let crease = sharpness(&(a, b), faces);
+        point_edges[a].push((b, crease));
+        point_edges[b].push((a, crease));
+    }
+
+    for (index, position) in positions.iter().enumerate() {
+        let incident = &point_edges[index];
+        let valence = incident.len();
+        let sharp: Vec<&(usize, f32)> = incident.iter().filter(|(_, c)| *c > 0.0).collect();
+        let smooth = || {
+            let faces = &point_faces[index];
+            let q = faces
+                .iter()
+                .fold(position.coords * 0.0, |sum, f| sum + face_points[*f].coords)
+                / faces.len() as f32;
+            let r = incident.iter().fold(position.coords * 0.0, |sum, (n, _)| {
+                sum + (position.coords + positions[*n].coords) * 0.5
+            }) / valence as f32;
+            let n = valence as f32;
+            Position::from((q + r * 2.0 + position.coords * (n - 3.0)) / n)
+        };
+        let moved = match sharp.as_slice() {
+            _ if valence == 0 || point_faces[index].is_empty() => *position,
+            // Corners of a single face on the boundary are kept.
+            [_, _] if valence == 2 && point_faces[index].len() == 1 => *position,
+            [] | [_] => smooth(),
+            [(n0, c0), (n1, c1)] => {
+                let crease = crease_rule(position, &positions[*n0], &positions[*n1]);
+                let weight = ((c0 + c1) * 0.5).min(1.0);
+                if weight < 1.0 {
+                    Position::from(smooth().coords.lerp(&crease.coords, weight))
+                } else {
+                    crease
+                }
+            }
+            _ => *position,
+        };
+        refined.positions.push(moved);
+    }
+
+    let face_offset = refined.positions.len();
+    refined.positions.extend(face_points.iter().cloned());
+
+    let mut edge_points: HashMap<EdgeKey, usize> = HashMap::new();
+    for (&(a, b), faces) in &edges {
+        let crease = sharpness(&(a, b), faces);
+        let midpoint = (positions[a].coords + positions[b].coords) * 0.5;
+        let position = if crease >= 1.0 {
+            midpoint
+        } else {
+            let smooth =
+                (midpoint * 2.0 + face_points[faces[0].0].coords + face_points[faces[1].0].coords)
+                    / 4.0;
+            smooth.lerp(&midpoint, crease)
+        };
+        refined.positions.push(Position::from(position));
+        edge_points.insert((a, b), refined.positions.len() - 1);
+        if let Some(crease) = polygons.creases.get(&(a, b)).filter(|c| **c > 1.0) {
+            let child = refined.positions.len() - 1;
+            refined.creases.insert(edge_key(a, child), crease - 1.0);
+            refined.creases.insert(edge_key(b, child), crease - 1.0);
+        }
+    }
+
+    for (face_index, (face, attrs)) in polygons
+        .faces
+        .iter()
+        .zip(polygons.attributes.iter())
+        .enumerate()
+    {
+        let count = face.len();
+        let face_point = face_offset + face_index;
+        let face_attrs = average_attributes(attrs);
+        let mids: Vec<usize> = (0..count)
+            .map(|corner| edge_points[&edge_key(face[corner], face[(corner + 1) % count])])
+            .collect();
+        let mid_attrs: Vec<VertexAttributes> = (0..count)
+            .map(|corner| {
+                VertexAttributes::blend(&[
+                    (&attrs[corner], 0.5),
+                    (&attrs[(corner + 1) % count], 0.5),
+                ])
+            })
+            .collect();
+        for corner in 0..count {
+            let previous = (corner + count - 1) % count;
+            refined
+                .faces
+                .push(vec![face[corner], mids[corner], face_point, mids[previous]]);
+            refined.attributes.push(vec![
+                attrs[corner].clone(),
+                mid_attrs[corner].clone(),
+                face_attrs.clone(),
+                mid_attrs[previous].clone(),
+            ]);
+        }
+    }
+
+    refined
+}
+
+/// Refines a polygon mesh using the Catmull-Clark subdivision scheme.
+///
+/// Every level turns each face with n corners into n quads. Edges on the
+/// boundary stay sharp. The crease of an edge (see `FullEdgeProxy::crease`)
+/// controls its sharpness: edges with a crease of one or more are
+/// subdivided as sharp creases and hand a crease reduced by one down to
+/// their halves, while values between zero and one blend between the
+/// smooth and sharp rules. Points where more than two sharp edges meet
+/// and corners of a single face on the boundary keep their position.
+pub fn subdivide_catmull_clark(mesh: &Mesh, levels: usize) -> Mesh {
+    let mut polygons = Polygons::from_mesh(mesh);
+    for _ in 0..levels {
+        polygons = catmull_clark_step(&polygons);
+    }
+    polygons.into_mesh()
+}
+
+/// Refines a triangle mesh using Loop's subdivision scheme.
+///
+/// Faces with more than three corners are split into triangle fans first.
+/// Boundary edges are treated as creases which are smoothed as cubic
+/// B-splines, and creased edges follow the same rules as in
+/// `subdivide_catmull_clark`. Points joining more than two sharp edges keep
+/// their position. Vertex attributes are linearly interpolated along the
+/// split edges.
+pub fn subdivide_loop(mesh: &Mesh, levels: usize) -> Mesh {
+    let mut polygons = Polygons::from_mesh(mesh).triangulated();
+    for _ in 0..levels {
+        polygons = loop_step(&polygons);
+    }
+    polygons.into_mesh()
+}
+
+/// Kobbelt's smoothing weight for an interior point of valence `n`.
+fn sqrt3_alpha(n: usize) -> f32 {
+    (4.0 - 2.0 * (2.0 * PI / n as f32).cos()) / 9.0
+}
+
+fn sqrt3_step(polygons: &Polygons) -> Polygons {
+    let edges = polygons.edges();
+    let positions = &polygons.positions;
+    let mut neighbors = vec![Vec::new(); positions.len()];
+    let mut on_boundary = vec![false; positions.len()];
+    for (&(a, b), faces) in &edges {
+        neighbors[a].push(b);
+        neighbors[b].push(a);
+        if faces.len() != 2 {
+            on_boundary[a] = true;
+            on_boundary[b] = true;
+        }
+    }
+
+    let mut refined = Polygons::default();
+    for (index, position) in positions.iter().enumerate() {
+        let valence = neighbors[index].len();
+        let moved = if on_boundary[index] || valence == 0 {
+            *position
+        } else {
+            let alpha = sqrt3_alpha(valence);
+            let sum = neighbors[index]
+                .iter()
+                .fold(position.coords * 0.0, |sum, n| sum + positions[*n].coords);
+            Position::from(position.coords * (1.0 - alpha) + sum * (alpha / valence as f32))
+        };
+        refined.positions.push(moved);
+    }
+
+    let face_offset = refined.positions.len();
+    let mut face_attrs = Vec::with_capacity(polygons.faces.len());
+    for (face, attrs) in polygons.faces.iter().zip(polygons.attributes.iter()) {
+        refined.positions.push(centroid(positions, face));
+        face_attrs.push(average_attributes(attrs));
+    }
+
+    for faces in edges.values() {
+        match faces.as_slice() {
+            [(f, corner), (g, _)] => {
+                // Replace the edge a -> b of face f by an edge between
+                // the centers of f and g.
+                let face = &polygons.faces[*f];
+                let attrs = &polygons.attributes[*f];
+                let next = (corner + 1) % 3;
+                let (a, b) = (face[*corner], face[next]);
+                let (cf, cg) = (face_offset + f, face_offset + g);
+                refined.faces.push(vec![a, cg, cf]);
+                refined.attributes.push(vec![
+                    attrs[*corner].clone(),
+                    face_attrs[*g].clone(),
+                    face_attrs[*f].clone(),
+                ]);
+                refined.faces.push(vec![cg, b, cf]);
+                refined.attributes.push(vec![
+                    face_attrs[*g].clone(),
+                    attrs[next].clone(),
+                    face_attrs[*f].clone(),
+                ]);
+            }
+            _ => {
+                for (f, corner) in faces {
+                    let face = &polygons.faces[*f];
+                    let attrs = &polygons.attributes[*f];
+                    let next = (corner + 1) % 3;
+                    refined
+                        .faces
+                        .push(vec![face[*corner], face[next], face_offset + f]);
+                    refined.attributes.push(vec![
+                        attrs[*corner].clone(),
+                        attrs[next].clone(),
+                        face_attrs[*f].clone(),
+                    ]);
+                }
+            }
+        }
+    }
+
+    refined
+}
+
+/// Refines a triangle mesh using Kobbelt's sqrt(3) subdivision scheme.
+///
+/// Each level inserts a point at the center of every triangle and flips
+/// the original edges, which triples the number of faces instead of
+/// quadrupling them. Faces with more than three corners are split into
+/// triangle fans first. Edges on the boundary are not flipped and the
+/// points on the boundary keep their position.
+pub fn subdivide_sqrt3(mesh: &Mesh, levels: usize) -> Mesh {
+    let mut polygons = Polygons::from_mesh(mesh).triangulated();
+    for _ in 0..levels {
+        polygons = sqrt3_step(&polygons);
+    }
+    polygons.into_mesh()
+}
+
+fn midpoint_step(polygons: &Polygons) -> Polygons {
+    let edges = polygons.edges();
+    let positions = &polygons.positions;
+    let mut refined = Polygons {
+        positions: positions.clone(),
+        ..Polygons::default()
+    };
+
+    let mut edge_points: HashMap<EdgeKey, usize> = HashMap::new();
+    for &(a, b) in edges.keys() {
+        let midpoint = (positions[a].coords + positions[b].coords) * 0.5;
+        refined.positions.push(Position::from(midpoint));
+        let child = refined.positions.len() - 1;
+        edge_points.insert((a, b), child);
+        if let Some(crease) = polygons.creases.get(&(a, b)) {
+            refined.creases.insert(edge_key(a, child), *crease);
+            refined.creases.insert(edge_key(b, child), *crease);
+        }
+    }
+
+    for (face, attrs) in polygons.faces.iter().zip(polygons.attributes.iter()) {
+        let count = face.len();
+        let mids: Vec<usize> = (0..count)
+            .map(|corner| edge_points[&edge_key(face[corner], face[(corner + 1) % count])])
+            .collect();
+        let mid_attrs: Vec<VertexAttributes> = (0..count)
+            .map(|corner| {
+                VertexAttributes::blend(&[
+                    (&attrs[corner], 0.5),
+                    (&attrs[(corner + 1) % count], 0.5),
+                ])
+            })
+            .collect();
+
+        if count == 3 {
+            for corner in 0..3 {
+                let previous = (corner + 2) % 3;
+                refined
+                    .faces
+                    .push(vec![face[corner], mids[corner], mids[previous]]);
+                refined.attributes.push(vec![
+                    attrs[corner].clone(),
+                    mid_attrs[corner].clone(),
+                    mid_attrs[previous].clone(),
+                ]);
+            }
+            refined.faces.push(mids);
+            refined.attributes.push(mid_attrs);
+        } else {
+            refined.positions.push(centroid(positions, face));
+            let center = refined.positions.len() - 1;
+            let center_attrs = average_attributes(attrs);
+            for corner in 0..count {
+                let previous = (corner + count - 1) % count;
+                refined
+                    .faces
+                    .push(vec![face[corner], mids[corner], center, mids[previous]]);
+                refined.attributes.push(vec![
+                    attrs[corner].clone(),
+                    mid_attrs[corner].clone(),
+                    center_attrs.clone(),
+                    mid_attrs[previous].clone(),
+                ]);
+            }
+        }
+    }
+
+    refined
+}
+
+/// Refines a mesh by splitting its edges at their midpoints without
+/// moving any points.
+///
+/// Triangles are split into four triangles, every other face with n
+/// corners into n quads around its centroid. The shape of triangle and
+/// planar faces is preserved exactly. Creases are handed down to both
+/// halves of an edge unchanged.
+pub fn subdivide_midpoint(mesh: &Mesh, levels: usize) -> Mesh {
+    let mut polygons = Polygons::from_mesh(mesh);
+    for _ in 0..levels {
+        polygons = midpoint_step(&polygons);
+    }
+    polygons.into_mesh()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn sqrt3_subdivision_of_icosahedron() {
+        let _ = env_logger::try_init();
+        let mesh = Mesh::icosphere(1.0, 0);
+
+        let refined = subdivide_sqrt3(&mesh, 1);
+        assert!(refined.is_valid());
+        assert_eq!(refined.face_count(), 60);
+        assert_eq!(refined.point_count(), 32);
+        assert_eq!(refined.full_edges().filter(|e| e.is_boundary()).count(), 0);
+
+        let refined = subdivide_sqrt3(&mesh, 2);
+        assert!(refined.is_valid());
+        assert_eq!(refined.face_count(), 180);
+        assert_eq!(refined.point_count(), 92);
+    }
+
+    #[test]
+    fn sqrt3_subdivision_keeps_boundary() {
+        let _ = env_logger::try_init();
+        let mesh = Mesh::grid(1.0, 1.0, 2, 2);
+
+        let refined = subdivide_sqrt3(&mesh, 1);
+        assert!(refined.is_valid());
+        assert_eq!(refined.face_count(), 2 * 8 + 8);
+        assert_eq!(refined.full_edges().filter(|e| e.is_boundary()).count(), 8);
+        assert!(refined.vertices().all(|v| v.point().position().y == 0.0));
+    }
+
+    #[test]
+    fn midpoint_subdivision_preserves_shape() {
+        let _ = env_logger::try_init();
+        let mesh = Mesh::unit_cube();
+
+        let refined = subdivide_midpoint(&mesh, 2);
+        assert!(refined.is_valid());
+        assert_eq!(refined.face_count(), 96);
+        assert_eq!(refined.full_edges().filter(|e| e.is_boundary()).count(), 0);
+        for vert in refined.vertices() {
+            assert!(is_on_unit_cube(&vert.point().position()));
+        }
+
+        let mesh = Mesh::icosphere(1.0, 0);
+        let refined = subdivide_midpoint(&mesh, 1);
+        assert!(refined.is_valid());
+        assert_eq!(refined.face_count(), 80);
+        assert_eq!(refined.point_count(), 42);
+        assert!(refined.faces().all(|f| f.vertices().count() == 3));
+    }
+
+    fn is_on_unit_cube(position: &Position) -> bool {
+        let coords = [position.x, position.y, position.z];
+        let inside = coords.iter().all(|c| *c > -1e-5 && *c < 1.0 + 1e-5);
+        let on_side = coords
+            .iter()
+            .any(|c| c.abs() < 1e-5 || (c - 1.0).abs() < 1e-5);
+        inside && on_side
+    }
+
+    #[test]
+    fn catmull_clark_subdivision_of_cube() {
+        let _ = env_logger::try_init();
+        let mesh = Mesh::unit_cube();
+
+        let refined = subdivide_catmull_clark(&mesh, 1);
+        assert!(refined.is_valid());
+        assert_eq!(refined.face_count(), 24);
+        assert_eq!(refined.point_count(), 26);
+        assert_eq!(refined.full_edges().filter(|e| e.is_boundary()).count(), 0);
+        assert!(refined.faces().all(|f| f.vertices().count() == 4));
+
+        let refined = subdivide_catmull_clark(&mesh, 2);
+        assert!(refined.is_valid());
+        assert_eq!(refined.face_count(), 96);
+        // Without creases the cube shrinks towards a sphere.
+        assert!(refined
+            .vertices()
+            .any(|v| !is_on_unit_cube(&v.point().position())));
+    }
+
+    #[test]
+    fn catmull_clark_turns_polygons_into_quads() {
+        let _ = env_logger::try_init();
+        let mesh = Mesh::cone(1.0, 1.0, 5, true);
+
+        let refined = subdivide_catmull_clark(&mesh, 1);
+        assert!(refined.is_valid());
+        assert_eq!(refined.face_count(), 5 * 3 + 5);
+        assert!(refined.faces().all(|f| f.vertices().count() == 4));
+        assert_eq!(refined.full_edges().filter(|e| e.is_boundary()).count(), 0);
+    }
+
+    #[test]
+    fn catmull_clark_keeps_boundary_corners() {
+        let _ = env_logger::try_init();
+        let mesh = Mesh::grid(2.0, 2.0, 1, 1);
+
+        let refined = subdivide_catmull_clark(&mesh, 2);
+        assert!(refined.is_valid());
+        assert_eq!(refined.face_count(), 16);
+        let corners = refined
+            .vertices()
+            .map(|v| v.point().position())
+            .filter(|p| p.x.abs() == 1.0 && p.z.abs() == 1.0)
+            .count();
+        assert!(corners >= 4);
+        assert!(refined.vertices().all(|v| v.point().position().y == 0.0));
+    }
+
+    #[test]
+    fn catmull_clark_respects_creases() {
+        let _ = env_logger::try_init();
+        let mesh = Mesh::unit_cube();
+        for edge in mesh.full_edges() {
+            edge.set_crease(f32::INFINITY);
+        }
+
+        let refined = subdivide_catmull_clark(&mesh, 2);
+        assert!(refined.is_valid());
+        assert_eq!(
+            refined.full_edges().filter(|e| e.crease() > 0.0).count(),
+            48
+        );
+        for vert in refined.vertices() {
+            assert!(is_on_unit_cube(&vert.point().position()));
+        }
+
+        // A crease of one only affects the first level.
+        let mesh = Mesh::unit_cube();
+        for edge in mesh.full_edges() {
+            edge.set_crease(1.0);
+        }
+        let refined = subdivide_catmull_clark(&mesh, 1);
+        assert_eq!(refined.full_edges().filter(|e| e.crease() > 0.0).count(), 0);
+        for vert in refined.vertices() {
+            assert!(is_on_unit_cube(&vert.point().position()));
+        }
+    }
+
+    #[test]
+    fn catmull_clark_interpolates_attributes() {
+        let _ = env_logger::try_init();
+        let mesh = Mesh::grid(1.0, 1.0, 1, 1);
+        let face = mesh.faces().next().unwrap();
+        for (index, vert) in face.vertices().enumerate() {
+            let mut data = vert.data_mut().unwrap();
+            data.attrs.color = Color::new(index as f32, 0.0, 0.0, 1.0);
+        }
+
+        let refined = subdivide_catmull_clark(&mesh, 1);
+        let mut colors: Vec<f32> = refined
+            .faces()
+            .flat_map(|f| f.vertices())
+            .map(|v| v.data().unwrap().attrs.color.x)
+            .collect();
+        colors.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        colors.dedup();
+        assert_eq!(colors, vec![0.0, 0.5, 1.0, 1.5, 2.0, 2.5, 3.0]);
+    }
+
+    #[test]
+    fn loop_subdivision_of_icosahedron() {
+        let _ = env_logger::try_init();
+        let mesh = Mesh::icosphere(1.0, 0);
+
+        let refined = subdivide_loop(&mesh, 2);
+        assert!(refined.is_valid());
+        assert_eq!(refined.face_count(), 320);
+        assert_eq!(refined.point_count(), 162);
+        assert_eq!(refined.full_edge_count(), 480);
+        assert_eq!(refined.full_edges().filter(|e| e.is_boundary()).count(), 0);
+
+        // The limit surface shrinks towards the center but stays round.
+        let radii: Vec<f32> = refined
+            .vertices()
+            .map(|v| v.point().position().coords.norm())
+            .collect();
+        let min = radii.iter().cloned().fold(f32::MAX, f32::min);
+        let max = radii.iter().cloned().fold(0.0, f32::max);
+        assert!(max < 1.0);
+        assert!(max - min < 0.05);
+    }
+
+    #[test]
+    fn loop_subdivision_respects_creases() {
+        let _ = env_logger::try_init();
+        let mesh = Mesh::icosphere(1.0, 0);
+        let edge = mesh.full_edges().next().unwrap();
+        edge.set_crease(f32::INFINITY);
+        let (p0, p1) = edge.points();
+        let midpoint = Position::from((p0.position().coords + p1.position().coords) * 0.5);
+
+        let refined = subdivide_loop(&mesh, 1);
+        assert!(refined.is_valid());
+        assert_eq!(refined.full_edges().filter(|e| e.crease() > 0.0).count(), 2);
+        // Sharp edges are split at their midpoint instead of being smoothed.
+        assert!(refined
+            .vertices()
+            .any(|v| (v.point().position() - midpoint).norm() < 1e-5));
+
+        // A crease of one only affects the first level.
+        let edge = mesh.full_edges().next().unwrap();
+        edge.set_crease(1.0);
+        let refined = subdivide_loop(&mesh, 1);
+        assert_eq!(refined.full_edges().filter(|e| e.crease() > 0.0).count(), 0);
+        assert!(refined
+            .vertices()
+            .any(|v| (v.point().position() - midpoint).norm() < 1e-5));
+    }
+
+    #[test]
+    fn loop_subdivision_keeps_planar_boundary() {
+        let _ = env_logger::try_init();
+        let positions = [
+            Position::new(0.0, 0.0, 0.0),
+            Position::new(1.0, 0.0, 0.0),
+            Position::new(1.0, 1.0, 0.0),
+            Position::new(0.0, 1.0, 0.0),
+        ];
+        let mesh = Mesh::from_polygons(&positions, &[[0, 1, 2, 3]]);
+
+        let refined = subdivide_loop(&mesh, 1);
+        assert!(refined.is_valid());
+        assert_eq!(refined.face_count(), 8);
+        assert_eq!(refined.point_count(), 9);
+        for vert in refined.vertices() {
+            let position = vert.point().position();
+            assert_eq!(position.z, 0.0);
+            assert!(position.x >= 0.0 && position.x <= 1.0);
+            assert!(position.y >= 0.0 && position.y <= 1.0);
+        }
+        let boundary_points = refined.full_edges().filter(|e| e.is_boundary()).count();
+        assert_eq!(boundary_points, 8);
+    }
+
+    #[test]
+    fn loop_subdivision_interpolates_attributes() {
+        let _ = env_logger::try_init();
+        let mut mesh = Mesh::default();
+        let p0 = mesh.add(Point::from_position(0.0, 0.0, 0.0));
+        let p1 = mesh.add(Point::from_position(1.0, 0.0, 0.0));
+        let p2 = mesh.add(Point::from_position(0.0, 1.0, 0.0));
+        let f0 = mesh.add_face([p0, p1, p2].as_ref());
+        for (index, vert) in mesh.face(f0).vertices().enumerate() {
+            let mut data = vert.data_mut().unwrap();
+            data.attrs.color = Color::new(index as f32, 0.0, 0.0, 1.0);
+        }
+
+        let refined = subdivide_loop(&mesh, 1);
+        assert_eq!(refined.face_count(), 4);
+        let mut colors: Vec<f32> = refined
+            .faces()
+            .flat_map(|f| f.vertices())
+            .map(|v| v.data().unwrap().attrs.color.x)
+            .collect();
+        colors.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        colors.dedup();
+        assert_eq!(colors, vec![0.0, 0.5, 1.0, 1.5, 2.0]);
+    }
+}
@@ -0,0 +1,13 @@
+//! Helpers shared by the unit tests.
+
+use crate::mesh::Mesh;
+
+/// Euler characteristic `V - E + F` counting full edges.
+pub fn euler_characteristic(mesh: &Mesh) -> isize {
+    mesh.point_count() as isize - mesh.full_edge_count() as isize + mesh.face_count() as isize
+}
+
+/// Number of full edges with a face on only one side.
+pub fn boundary_edge_count(mesh: &Mesh) -> usize {
+    mesh.full_edges().filter(|edge| edge.is_boundary()).count()
+}
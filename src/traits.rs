@@ -77,10 +77,12 @@ pub trait AddFace<A> {
     fn add_face(&mut self, args: A) -> FaceHandle;
 }
 
-// pub trait Bridge<A> {
-//     fn bridge(&mut self, args: A) -> FaceHandle;
-// }
+/// Interface for connecting two loops of edges, returning the faces in between.
+pub trait Bridge<A> {
+    fn bridge(&mut self, args: A) -> Vec<FaceHandle>;
+}
 
-// pub trait Extrude<A> {
-//     fn extrude(&mut self, args: A) -> FaceHandle;
-// }
+/// Interface for extruding faces, returning the faces created on the sides.
+pub trait Extrude<A> {
+    fn extrude(&mut self, args: A) -> Vec<FaceHandle>;
+}
@@ -0,0 +1,371 @@
+//! Splitting of faces into triangles and pairing triangles into quads.
+
+use std::collections::HashSet;
+
+use nalgebra as na;
+
+use crate::data::{Normal, Position, Triangulation};
+use crate::handles::{FaceHandle, HalfEdgeHandle};
+use crate::mesh::Mesh;
+use crate::proxy::*;
+use crate::traits::*;
+
+/// How far the corner at `index` bends towards the normal, positive for
+/// convex corners.
+fn convexity(positions: &[Position], normal: &Normal, index: usize) -> f32 {
+    let count = positions.len();
+    let prev = positions[(index + count - 1) % count];
+    let next = positions[(index + 1) % count];
+    let current = positions[index];
+    (current - prev).cross(&(next - current)).dot(normal)
+}
+
+/// Whether the triangle at the corner `index` can be cut off without
+/// overlapping the rest of the polygon.
+fn is_ear(positions: &[Position], normal: &Normal, index: usize) -> bool {
+    let count = positions.len();
+    if convexity(positions, normal, index) <= 0.0 {
+        return false;
+    }
+    let (a, b, c) = (
+        positions[(index + count - 1) % count],
+        positions[index],
+        positions[(index + 1) % count],
+    );
+    let inside = |q: &Position| {
+        [(a, b), (b, c), (c, a)]
+            .iter()
+            .all(|(from, to)| (to - from).cross(&(q - from)).dot(normal) >= 0.0)
+    };
+    positions
+        .iter()
+        .filter(|q| **q != a && **q != b && **q != c)
+        .all(|q| !inside(q))
+}
+
+/// Length of the diagonal cutting off the corner at `index`.
+fn diagonal(positions: &[Position], index: usize) -> f32 {
+    let count = positions.len();
+    let prev = positions[(index + count - 1) % count];
+    let next = positions[(index + 1) % count];
+    (next - prev).norm()
+}
+
+/// Largest angle between the normals of two triangles joined into a quad.
+const MAX_FOLD_ANGLE: f32 = std::f32::consts::PI * 2.0 / 9.0;
+/// Largest deviation of a quad corner from a right angle.
+const MAX_CORNER_DEVIATION: f32 = std::f32::consts::PI * 2.0 / 9.0;
+
+/// Rates the quad made of the corners, lower being better.
+///
+/// The score adds the fold between the two triangles to the average
+/// deviation of the corners from a right angle. Quads which are concave or
+/// exceed one of the limits get no score.
+fn quad_score(corners: &[Position; 4]) -> Option<f32> {
+    let n0 = (corners[1] - corners[0]).cross(&(corners[2] - corners[0]));
+    let n1 = (corners[2] - corners[0]).cross(&(corners[3] - corners[0]));
+    let (n0, n1) = (
+        n0.try_normalize(f32::EPSILON)?,
+        n1.try_normalize(f32::EPSILON)?,
+    );
+    let fold = n0.dot(&n1).clamp(-1.0, 1.0).acos();
+    if fold > MAX_FOLD_ANGLE {
+        return None;
+    }
+    let normal = n0 + n1;
+    let mut deviation = 0.0;
+    for index in 0..4 {
+        if convexity(corners, &normal, index) <= 0.0 {
+            return None;
+        }
+        let current = corners[index];
+        let to_prev = corners[(index + 3) % 4] - current;
+        let to_next = corners[(index + 1) % 4] - current;
+        let angle = to_prev.angle(&to_next);
+        let corner_deviation = (angle - std::f32::consts::FRAC_PI_2).abs();
+        if corner_deviation > MAX_CORNER_DEVIATION {
+            return None;
+        }
+        deviation += corner_deviation;
+    }
+    Some(fold + deviation / 4.0)
+}
+
+impl Mesh {
+    /// Splits a face into triangles by adding edges between its corners.
+    ///
+    /// Returns the triangles, starting with the face itself which keeps
+    /// one of them. Faces with three corners are left as they are.
+    pub fn triangulate_face(&mut self, face: FaceHandle, method: Triangulation) -> Vec<FaceHandle> {
+        if !self.face(face).is_valid() {
+            log::warn!("Unable to triangulate invalid face {}.", face.index());
+            return Vec::new();
+        }
+        let apex = self.face(face).root_edge().vertex().point().handle;
+        let mut triangles = vec![face];
+        loop {
+            let corners: Vec<HalfEdgeHandle> =
+                self.face(face).edges().map(|edge| edge.handle).collect();
+            let count = corners.len();
+            if count <= 3 {
+                break;
+            }
+            let positions: Vec<Position> = corners
+                .iter()
+                .map(|edge| self.edge(*edge).vertex().point().position())
+                .collect();
+            let mut normal: Normal = na::zero();
+            for (index, current) in positions.iter().enumerate() {
+                normal += current.coords.cross(&positions[(index + 1) % count].coords);
+            }
+
+            let by_convexity = |a: &usize, b: &usize| {
+                let (a, b) = (
+                    convexity(&positions, &normal, *a),
+                    convexity(&positions, &normal, *b),
+                );
+                a.partial_cmp(&b).unwrap_or(std::cmp::Ordering::Equal)
+            };
+            let ears: Vec<usize> = (0..count)
+                .filter(|index| is_ear(&positions, &normal, *index))
+                .collect();
+            let ear = match method {
+                Triangulation::Fan => corners
+                    .iter()
+                    .position(|edge| self.edge(*edge).vertex().point().handle == apex)
+                    .map(|index| (index + 1) % count)
+                    .unwrap_or(1),
+                Triangulation::EarClipping => ears
+                    .first()
+                    .cloned()
+                    .or_else(|| (0..count).max_by(by_convexity))
+                    .unwrap_or(1),
+                Triangulation::ShortestDiagonal => {
+                    let candidates = if ears.is_empty() {
+                        (0..count).collect()
+                    } else {
+                        ears
+                    };
+                    candidates
+                        .into_iter()
+                        .min_by(|a, b| {
+                            diagonal(&positions, *a)
+                                .partial_cmp(&diagonal(&positions, *b))
+                                .unwrap_or(std::cmp::Ordering::Equal)
+                        })
+                        .unwrap_or(1)
+                }
+            };
+            let (from, to) = (
+                corners[(ear + 1) % count],
+                corners[(ear + count - 1) % count],
+            );
+            triangles.push(self.connect_corners(from, to).1);
+        }
+        triangles
+    }
+
+    /// Splits all faces with more than three corners into triangles.
+    ///
+    /// Returns the number of faces added.
+    pub fn triangulate(&mut self, method: Triangulation) -> usize {
+        let faces: Vec<_> = self
+            .faces()
+            .filter(|face| face.edges().count() > 3)
+            .map(|face| face.handle)
+            .collect();
+        faces
+            .into_iter()
+            .map(|face| self.triangulate_face(face, method).len() - 1)
+            .sum()
+    }
+
+    /// Joins pairs of neighboring triangles into quads.
+    ///
+    /// Pairs are rated by how flat and how close to rectangular their quad
+    /// would be, and joined greedily starting with the best one. Triangles
+    /// without a suitable partner are left as they are. Returns the number
+    /// of quads created.
+    pub fn quadrangulate(&mut self) -> usize {
+        let is_triangle = |face: &FaceProxy| face.is_valid() && face.edges().count() == 3;
+        let mut candidates: Vec<(f32, HalfEdgeHandle)> = self
+            .full_edges()
+            .filter_map(|edge| {
+                let h = edge.half_edge();
+                let t = h.adjacent();
+                if !is_triangle(&h.face()) || !is_triangle(&t.face()) {
+                    return None;
+                }
+                // The quad in the order of the remaining loop of `h`'s face.
+                let corners = [
+                    h.prev().vertex().point().position(),
+                    h.vertex().point().position(),
+                    t.prev().vertex().point().position(),
+                    t.vertex().point().position(),
+                ];
+                quad_score(&corners).map(|score| (score, h.handle))
+            })
+            .collect();
+        candidates.sort_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+        let mut joined = HashSet::new();
+        let mut count = 0;
+        for (_, edge) in candidates {
+            let (f, g) = {
+                let edge = self.edge(edge);
+                (edge.face().handle, edge.adjacent().face().handle)
+            };
+            if joined.contains(&f.index()) || joined.contains(&g.index()) {
+                continue;
+            }
+            if self.join_faces(edge).is_some() {
+                joined.insert(f.index());
+                joined.insert(g.index());
+                count += 1;
+            }
+        }
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    fn is_triangle_mesh(mesh: &Mesh) -> bool {
+        mesh.faces().all(|face| face.vertices().count() == 3)
+    }
+
+    #[test]
+    fn can_triangulate_cube() {
+        let _ = env_logger::try_init();
+        for method in [
+            Triangulation::Fan,
+            Triangulation::EarClipping,
+            Triangulation::ShortestDiagonal,
+        ]
+        .iter()
+        {
+            let mut mesh = Mesh::unit_cube();
+            assert_eq!(mesh.triangulate(*method), 6);
+            assert!(mesh.is_valid());
+            assert!(is_triangle_mesh(&mesh));
+            assert_eq!(mesh.face_count(), 12);
+            assert_eq!(mesh.full_edge_count(), 18);
+            assert_eq!(mesh.point_count(), 8);
+        }
+    }
+
+    #[test]
+    fn fan_shares_the_root_corner() {
+        let _ = env_logger::try_init();
+        let positions: Vec<_> = (0..6)
+            .map(|index| {
+                let angle = index as f32 * std::f32::consts::PI / 3.0;
+                Position::new(angle.cos(), 0.0, -angle.sin())
+            })
+            .collect();
+        let mut mesh = Mesh::from_polygons(&positions, &[[0, 1, 2, 3, 4, 5]]);
+        let face = mesh.faces().next().unwrap().handle;
+        let apex = mesh.face(face).root_edge().vertex().point().handle;
+        let triangles = mesh.triangulate_face(face, Triangulation::Fan);
+        assert!(mesh.is_valid());
+        assert_eq!(triangles.len(), 4);
+        for triangle in triangles {
+            assert!(mesh
+                .face(triangle)
+                .vertices()
+                .any(|vert| vert.point().handle == apex));
+        }
+    }
+
+    #[test]
+    fn ear_clipping_handles_concave_faces() {
+        let _ = env_logger::try_init();
+        // An arrow pointing along X, concave at the origin.
+        let positions = [
+            Position::new(0.0, 0.0, 0.0),
+            Position::new(-1.0, 0.0, 1.0),
+            Position::new(2.0, 0.0, 0.0),
+            Position::new(-1.0, 0.0, -1.0),
+        ];
+        for method in [Triangulation::EarClipping, Triangulation::ShortestDiagonal].iter() {
+            let mut mesh = Mesh::from_polygons(&positions, &[[0, 1, 2, 3]]);
+            mesh.triangulate(*method);
+            assert!(mesh.is_valid());
+            assert_eq!(mesh.face_count(), 2);
+            // The diagonal has to run through the concave corner.
+            let origin = mesh
+                .vertices()
+                .find(|vert| vert.point().position() == Position::origin())
+                .unwrap()
+                .point()
+                .handle;
+            let faces_at_origin = mesh
+                .faces()
+                .filter(|face| face.vertices().any(|vert| vert.point().handle == origin))
+                .count();
+            assert_eq!(faces_at_origin, 2);
+        }
+    }
+
+    #[test]
+    fn shortest_diagonal_splits_quads_along_shorter_diagonal() {
+        let _ = env_logger::try_init();
+        let positions = [
+            Position::new(-2.0, 0.0, 0.0),
+            Position::new(0.0, 0.0, 1.0),
+            Position::new(2.0, 0.0, 0.0),
+            Position::new(0.0, 0.0, -1.0),
+        ];
+        let mut mesh = Mesh::from_polygons(&positions, &[[0, 1, 2, 3]]);
+        mesh.triangulate(Triangulation::ShortestDiagonal);
+        assert!(mesh.is_valid());
+        let longest = mesh
+            .full_edges()
+            .map(|edge| {
+                let (p0, p1) = edge.points();
+                (p1.position() - p0.position()).norm()
+            })
+            .fold(0.0, f32::max);
+        assert!((longest - 5.0f32.sqrt()).abs() < 1e-5);
+    }
+
+    #[test]
+    fn quadrangulate_restores_triangulated_quads() {
+        let _ = env_logger::try_init();
+        let mut mesh = Mesh::grid(2.0, 2.0, 3, 3);
+        mesh.triangulate(Triangulation::Fan);
+        assert_eq!(mesh.face_count(), 18);
+        assert_eq!(mesh.quadrangulate(), 9);
+        assert!(mesh.is_valid());
+        assert_eq!(mesh.face_count(), 9);
+        assert!(mesh.faces().all(|face| face.vertices().count() == 4));
+        assert_eq!(mesh.full_edge_count(), 24);
+
+        let mut cube = Mesh::unit_cube();
+        cube.triangulate(Triangulation::ShortestDiagonal);
+        assert_eq!(cube.quadrangulate(), 6);
+        assert!(cube.is_valid());
+        assert_eq!(cube.face_count(), 6);
+    }
+
+    #[test]
+    fn quadrangulate_leaves_poor_pairs_as_triangles() {
+        let _ = env_logger::try_init();
+        let mut mesh = Mesh::icosphere(1.0, 0);
+        assert_eq!(mesh.quadrangulate(), 0);
+        assert_eq!(mesh.face_count(), 20);
+
+        // Two triangles folded by 90 degrees along their shared edge.
+        let positions = [
+            Position::new(0.0, 0.0, 0.0),
+            Position::new(1.0, 0.0, 0.0),
+            Position::new(1.0, 1.0, 0.0),
+            Position::new(0.0, 0.0, 1.0),
+        ];
+        let mut mesh = Mesh::from_polygons(&positions, &[[0, 1, 2], [1, 0, 3]]);
+        assert_eq!(mesh.quadrangulate(), 0);
+        assert_eq!(mesh.face_count(), 2);
+    }
+}